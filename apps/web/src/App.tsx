@@ -24,6 +24,7 @@ import {
   placeBet,
   type Choice,
 } from "./lib/program";
+import { verifyFrame } from "./lib/roundCodec";
 import { deriveUiRoundView } from "./lib/uiState";
 import { findOrCreateProfile, publishBetEvent } from "./lib/tapestry";
 import { FeedPage } from "./pages/FeedPage";
@@ -31,6 +32,7 @@ import { ProfilePage } from "./pages/ProfilePage";
 import type { CrankStatus } from "./types/contracts";
 import type { RoundStateV1, WsEvent } from "./types/ws";
 
+// Placeholder until the first round arrives; 20x20 is the default board size.
 const EMPTY_BOARD = new Array(400).fill(0);
 const BETTING_WINDOW_FALLBACK_SECONDS = 45;
 const GAME_WINDOW_SECONDS = 300;
@@ -353,6 +355,18 @@ export default function App() {
 
     setRoundState(nextState);
 
+    if (prevState) {
+      verifyFrame(prevState, nextState)
+        .then((ok) => {
+          if (ok === false) {
+            addEvent(
+              `Round ${nextState.roundId} move ${nextState.moveCount} does not match its state hash`
+            );
+          }
+        })
+        .catch(() => {});
+    }
+
     const derivedClock = derivePhaseClockFromRoundState(nextState);
     if (derivedClock) {
       setPhaseClock((prev) => {
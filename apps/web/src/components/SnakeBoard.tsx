@@ -12,36 +12,74 @@ type Props = {
   resultFx?: BoardResultFx;
 };
 
-const BOARD_SIZE = 20;
+// Shared-board rounds draw Beta as 4 (body) / 9 (head) next to Alpha's 3 / 8.
+const BETA_BODY = 4;
+const BETA_HEAD = 9;
+
+// Map obstacles and the shrinking border.
+const WALL = 1;
+// Special food kinds; see FoodOdds in the program.
+const FOOD = 2;
+const GOLDEN = 10;
+const POISON = 11;
+const BONUS = 12;
+
+function isFood(v: number) {
+  return v === FOOD || v === GOLDEN || v === POISON || v === BONUS;
+}
+
+// Boards are square; the round's board size is not sent separately.
+function boardSize(cells: number[]) {
+  return Math.max(1, Math.round(Math.sqrt(cells.length)));
+}
 
-function classForCell(v: number) {
-  if (v === 2) return "cell food";
-  if (v >= 3 && v <= 7) return "cell snake body";
-  if (v >= 8) return "cell snake head";
+function isSharedBoard(cells: number[]) {
+  return cells.some((cell) => cell === BETA_BODY || cell === BETA_HEAD);
+}
+
+function classForCell(v: number, shared: boolean) {
+  if (v === WALL) return "cell wall";
+  if (v === FOOD) return "cell food";
+  if (v === GOLDEN) return "cell food golden";
+  if (v === POISON) return "cell food poison";
+  if (v === BONUS) return "cell food bonus";
+  if (v === BETA_BODY) return "cell snake body beta-snake";
+  if (v === BETA_HEAD) return "cell snake head beta-snake";
+  const owner = shared ? " alpha-snake" : "";
+  if (v >= 3 && v <= 7) return `cell snake body${owner}`;
+  if (v >= 8) return `cell snake head${owner}`;
   return "cell";
 }
 
-function findHeadIndex(cells: number[]): number | null {
-  const index = cells.findIndex((cell) => cell >= 8);
+function findHeadIndex(cells: number[], side: "alpha" | "beta"): number | null {
+  const shared = isSharedBoard(cells);
+  const index = cells.findIndex((cell) =>
+    shared && side === "beta" ? cell === BETA_HEAD : cell === 8
+  );
   return index >= 0 ? index : null;
 }
 
 function deriveMoveDirection(
+  size: number,
   previousHead: number | null,
   nextHead: number | null
 ): string {
   if (previousHead == null || nextHead == null) return "--";
   if (previousHead === nextHead) return "Hold";
 
-  const prevX = previousHead % BOARD_SIZE;
-  const prevY = Math.floor(previousHead / BOARD_SIZE);
-  const nextX = nextHead % BOARD_SIZE;
-  const nextY = Math.floor(nextHead / BOARD_SIZE);
+  const prevX = previousHead % size;
+  const prevY = Math.floor(previousHead / size);
+  const nextX = nextHead % size;
+  const nextY = Math.floor(nextHead / size);
+
+  // Modulo the board size, so moves across the edge of a toroidal board count.
+  const dx = (nextX - prevX + size) % size;
+  const dy = (nextY - prevY + size) % size;
 
-  if (nextX === prevX + 1 && nextY === prevY) return "Right";
-  if (nextX === prevX - 1 && nextY === prevY) return "Left";
-  if (nextX === prevX && nextY === prevY - 1) return "Up";
-  if (nextX === prevX && nextY === prevY + 1) return "Down";
+  if (dx === 1 && dy === 0) return "Right";
+  if (dx === size - 1 && dy === 0) return "Left";
+  if (dx === 0 && dy === size - 1) return "Up";
+  if (dx === 0 && dy === 1) return "Down";
   return "--";
 }
 
@@ -63,7 +101,7 @@ export function SnakeBoard({
     if (previous && previous.length === board.length) {
       const disappearedFood: number[] = [];
       for (let i = 0; i < board.length; i += 1) {
-        if (previous[i] === 2 && board[i] !== 2) {
+        if (isFood(previous[i]) && board[i] !== previous[i]) {
           disappearedFood.push(i);
         }
       }
@@ -82,13 +120,15 @@ export function SnakeBoard({
 
     previousBoardRef.current = board.slice();
 
-    const nextHead = findHeadIndex(board);
-    const direction = deriveMoveDirection(previousHeadRef.current, nextHead);
+    const nextHead = findHeadIndex(board, side);
+    // A new round may use a different size; its first frame has no direction.
+    const previousHead = previous?.length === board.length ? previousHeadRef.current : null;
+    const direction = deriveMoveDirection(boardSize(board), previousHead, nextHead);
     if (direction !== "--") {
       setMoveDirection(direction);
     }
     previousHeadRef.current = nextHead;
-  }, [board]);
+  }, [board, side]);
 
   useEffect(() => {
     return () => {
@@ -103,6 +143,8 @@ export function SnakeBoard({
     [foodBlastIndices]
   );
 
+  const shared = isSharedBoard(board);
+  const size = boardSize(board);
   const icon = side === "alpha" ? "circle" : "change_history";
   const fxClass = resultFx ? `board-fx board-fx-${resultFx}` : "";
   const sideClass = `board-${side}`;
@@ -122,9 +164,14 @@ export function SnakeBoard({
           </div>
         </div>
       </header>
-      <div className="grid" role="img" aria-label={`${title} board`}>
+      <div
+        className="grid"
+        role="img"
+        aria-label={`${title} board`}
+        style={{ gridTemplateColumns: `repeat(${size}, minmax(0, 1fr))` }}
+      >
         {board.map((cell, i) => {
-          const baseClass = classForCell(cell);
+          const baseClass = classForCell(cell, shared);
           const blastClass = blastIndexSet.has(i) ? " food-blast" : "";
           return <div key={i} className={`${baseClass}${blastClass}`} />;
         })}
@@ -38,6 +38,7 @@ function parseRoundState(value: unknown): RoundStateV1 | null {
     status === "Active" ||
     status === "InProgress" ||
     status === "Settled" ||
+    status === "Voided" ||
     status === "Unknown"
       ? status
       : "Unknown";
@@ -68,6 +69,7 @@ function parseRoundState(value: unknown): RoundStateV1 | null {
     betaAlive: asBoolean(obj.betaAlive),
     alphaBoard,
     betaBoard,
+    stateHash: asString(obj.stateHash),
   };
 }
 
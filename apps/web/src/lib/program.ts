@@ -3,10 +3,11 @@ import { AnchorProvider, BN, Program } from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram, type Connection } from "@solana/web3.js";
 import idl from "../idl/magic_bet.json";
 import { APP_CONFIG } from "./config";
+import { decodeRoundBoards } from "./roundCodec";
 
 const CONFIG_SEED = "config_v2";
 const HOUSE_SEED = "house_v2";
-const ROUND_SEED = "round_v2";
+const ROUND_SEED = "round_v3";
 const BET_SEED = "bet_v2";
 const VAULT_SEED = "vault_v2";
 const textEncoder = new TextEncoder();
@@ -108,7 +109,12 @@ export async function placeBet(
   amountLamports: BN
 ) {
   return (program.methods as any)
-    .placeBet(new BN(roundId.toString()), toChoiceArg(choice), amountLamports)
+    .placeBet(
+      new BN(roundId.toString()),
+      toChoiceArg(choice),
+      amountLamports,
+      Array.from(crypto.getRandomValues(new Uint8Array(32)))
+    )
     .accountsPartial({
       user,
       config: configPda(program.programId),
@@ -139,10 +145,25 @@ export async function claimWinnings(
     .rpc();
 }
 
+const ROUND_STATUSES = ["active", "inProgress", "settled", "voided"];
+const ROUND_WINNERS = ["alpha", "beta", "draw"];
+
+// Round is a zero-copy account: status is its enum index and winner is 0 until
+// the round resolves, then 1 + the AIChoice index. Packed boards follow the
+// header; see roundCodec.
 export async function fetchRound(program: Program, roundId: bigint) {
-  return (program.account as any).round.fetchNullable(
+  const info = await program.provider.connection.getAccountInfo(
     roundPda(program.programId, roundId)
   );
+  if (!info) return null;
+  const round = program.coder.accounts.decode("Round", info.data);
+  return {
+    ...round,
+    ...decodeRoundBoards(info.data, round.boardSize),
+    status: { [ROUND_STATUSES[round.status] ?? "unknown"]: {} },
+    winner:
+      round.winner === 0 ? null : { [ROUND_WINNERS[round.winner - 1]]: {} },
+  };
 }
 
 export async function fetchBet(program: Program, roundId: bigint, user: PublicKey) {
@@ -0,0 +1,71 @@
+import type { RoundStateV1 } from "../types/ws";
+
+// Off-chain view of the zero-copy Round account: an Anchor-decoded header
+// followed by alpha_board and beta_board, packed two cells per byte (even cells
+// in the low nibble), then both snake body rings (u16 LE per cell).
+
+export const packedBoardLen = (cells: number) => Math.ceil(cells / 2);
+
+// One number per cell, row-major, using the same cell values as the crank's
+// round_state_v1 boards.
+export function unpackBoard(bytes: Uint8Array, cells: number): number[] {
+  return Array.from({ length: cells }, (_, i) => (bytes[i >> 1] >> (4 * (i & 1))) & 0x0f);
+}
+
+// Inverse of `unpackBoard`; an odd cell count leaves the last high nibble 0.
+export function packBoard(board: number[]): Uint8Array {
+  const bytes = new Uint8Array(packedBoardLen(board.length));
+  board.forEach((cell, i) => {
+    bytes[i >> 1] |= cell << (4 * (i & 1));
+  });
+  return bytes;
+}
+
+const hexToBytes = (hex: string) =>
+  Uint8Array.from(hex.match(/../g) ?? [], (pair) => parseInt(pair, 16));
+
+// Checks that `next` is the frame right after `prev` and hashes to the
+// on-chain state hash it reports: sha256(prev hash, move count, both packed
+// boards, alpha and beta score), integers as u32 LE. Frames that are not
+// consecutive (batched moves, reconnects) cannot be checked and return null.
+export async function verifyFrame(
+  prev: RoundStateV1,
+  next: RoundStateV1
+): Promise<boolean | null> {
+  if (
+    prev.roundId !== next.roundId ||
+    next.moveCount !== prev.moveCount + 1 ||
+    !prev.stateHash ||
+    !next.stateHash
+  ) {
+    return null;
+  }
+  const alphaBoard = packBoard(next.alphaBoard);
+  const betaBoard = packBoard(next.betaBoard);
+  const input = new Uint8Array(32 + 4 + alphaBoard.length + betaBoard.length + 8);
+  const view = new DataView(input.buffer);
+  input.set(hexToBytes(prev.stateHash), 0);
+  view.setUint32(32, next.moveCount, true);
+  input.set(alphaBoard, 36);
+  input.set(betaBoard, 36 + alphaBoard.length);
+  view.setUint32(36 + 2 * alphaBoard.length, next.alphaScore, true);
+  view.setUint32(40 + 2 * alphaBoard.length, next.betaScore, true);
+  const digest = new Uint8Array(await crypto.subtle.digest("SHA-256", input));
+  const hex = Array.from(digest, (byte) => byte.toString(16).padStart(2, "0")).join("");
+  return hex === next.stateHash;
+}
+
+// The boards and bodies fill the end of the account, so they can be located
+// from the board size alone.
+export function decodeRoundBoards(data: Uint8Array, boardSize: number) {
+  const cells = boardSize * boardSize;
+  const boardLen = packedBoardLen(cells);
+  const boardsStart = data.length - 4 * cells - 2 * boardLen;
+  return {
+    alphaBoard: unpackBoard(data.subarray(boardsStart, boardsStart + boardLen), cells),
+    betaBoard: unpackBoard(
+      data.subarray(boardsStart + boardLen, boardsStart + 2 * boardLen),
+      cells
+    ),
+  };
+}
@@ -5,6 +5,7 @@ export type UiRoundState =
   | "BettingOpen"
   | "InProgress"
   | "Settled"
+  | "Voided"
   | "Claimable"
   | "Claimed";
 
@@ -87,6 +88,16 @@ export function deriveUiRoundView(params: Params): UiRoundView {
     };
   }
 
+  if (roundState.status === "Voided") {
+    return {
+      state: "Voided",
+      statusLabel: "Round Voided - Bets Refunded",
+      canPlaceBet: false,
+      canClaim: false,
+      bannerTone: "neutral",
+    };
+  }
+
   return {
     state: "Idle",
     statusLabel: "Waiting for round",
@@ -2,7 +2,7 @@ export type RoundStateV1 = {
   type: "round_state_v1";
   ts: number;
   roundId: string;
-  status: "Active" | "InProgress" | "Settled" | "Unknown";
+  status: "Active" | "InProgress" | "Settled" | "Voided" | "Unknown";
   moveCount: number;
   winner: "Alpha" | "Beta" | "Draw" | null;
   alphaScore: number;
@@ -11,6 +11,7 @@ export type RoundStateV1 = {
   betaAlive: boolean;
   alphaBoard: number[];
   betaBoard: number[];
+  stateHash: string;
 };
 
 export type RoundTransitionV1 = {
@@ -1,40 +1,194 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar::slot_hashes;
 use anchor_lang::system_program::{self, Transfer};
 use ephemeral_rollups_sdk::anchor::{commit, delegate, ephemeral};
 use ephemeral_rollups_sdk::cpi::DelegateConfig;
 use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
+use solana_program::compute_units::sol_remaining_compute_units;
+use solana_sha256_hasher::hashv;
 
 declare_id!("DXaehEyGPBunzm3X5p3tCwcZVhx9dX8mnU7cfekvm5D2");
 
 const CONFIG_SEED: &[u8] = b"config_v2";
 const HOUSE_SEED: &[u8] = b"house_v2";
-const ROUND_SEED: &[u8] = b"round_v2";
+const ROUND_SEED: &[u8] = b"round_v3";
+// Rounds created before the zero-copy layout; see `migrate_legacy_round`.
+const LEGACY_ROUND_SEED: &[u8] = b"round_v2";
+// Cells per board in a legacy round, which was always BOARD_SIZE square.
+const LEGACY_BOARD_CELLS: usize = BOARD_SIZE * BOARD_SIZE;
 const BET_SEED: &[u8] = b"bet_v2";
 const VAULT_SEED: &[u8] = b"vault_v2";
-
-const BOARD_SIZE: usize = 20;
-const BOARD_CELLS: usize = BOARD_SIZE * BOARD_SIZE;
-const MAX_MOVES: u32 = 300;
+const TEMPLATE_SEED: &[u8] = b"template";
+const MOVE_LOG_SEED: &[u8] = b"move_log";
+const IDENTITY_SEED: &[u8] = b"identity";
+
+// The only oracle a VRF round may name. Pinned so whoever creates a round
+// cannot point it at a program they control and pick its seeds; localnet
+// builds select `programs/mock_vrf_oracle` with the `mock-vrf` feature.
+// `request_round_randomness` speaks the mock's request interface, so only
+// those builds take VRF rounds; see `VRF_ROUNDS_SUPPORTED`.
+#[cfg(not(feature = "mock-vrf"))]
+const VRF_ORACLE_PROGRAM_ID: Pubkey = pubkey!("Vrf1RNUjXmQGjmQrQLvJHs9SNkvDJEsRVFPkfSQUwGz");
+#[cfg(feature = "mock-vrf")]
+const VRF_ORACLE_PROGRAM_ID: Pubkey = pubkey!("GskmsTtmpZunEVeP5weEVGsjr1HVXjjkqS1FTHuDn78K");
+
+// Anchor discriminator of the mock oracle's `request_randomness` instruction.
+const VRF_REQUEST_DISCRIMINATOR: [u8; 8] = [213, 5, 173, 166, 37, 236, 31, 18];
+// Whether create_round accepts `SeedSource::Vrf`. The pinned MagicBlock oracle
+// takes a different request (with an oracle queue account) than the mock, so
+// until the request is built from its SDK a VRF round there could never be
+// seeded and would always void.
+const VRF_ROUNDS_SUPPORTED: bool = cfg!(feature = "mock-vrf");
+
+// Boards are square, `RoundRules.board_size` cells a side, stored row-major and
+// packed two cells per byte (see `Board`), so every cell value must fit in 4 bits.
+const BOARD_SIZE: usize = 20; // default
+const MIN_BOARD_SIZE: usize = 10;
+const MAX_BOARD_SIZE: usize = 32;
+const MAX_BOARD_CELLS: usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE;
+// Ceiling for `RoundRules.max_moves`: the ticks a one-item round's move log has
+// room for. More food lowers it; see `max_logged_moves`.
+const MAX_MOVES_CAP: u32 = max_logged_moves(1);
+// Ticks one execute_moves call may be asked for. The call itself stops early
+// once less than TICK_COMPUTE_RESERVE compute units are left, so a batch never
+// runs out of budget halfway through a tick whatever the board size.
+const MAX_MOVES_PER_BATCH: u8 = 16;
+// Compute units execute_moves wants in hand before starting another tick. The
+// ER tests hold the costliest ticks (32x32, path-searching AIs, every food slot
+// respawning on both boards) under it.
+const TICK_COMPUTE_RESERVE: u64 = 150_000;
 
 const CELL_EMPTY: u8 = 0;
 const CELL_WALL: u8 = 1;
 const CELL_FOOD: u8 = 2;
-const SNAKE_MIN: u8 = 3;
-const SNAKE_MAX: u8 = 8;
-
+// Same values the old countdown encoding used for tail and head, so existing
+// board renderers keep working.
+const CELL_BODY: u8 = 3;
+const CELL_HEAD: u8 = 8;
+// Beta's cells on a shared board, so both snakes can be told apart.
+const CELL_BETA_BODY: u8 = 4;
+const CELL_BETA_HEAD: u8 = 9;
+// Special food, rolled per spawn from the round's `FoodOdds`.
+const CELL_GOLDEN: u8 = 10;
+const CELL_POISON: u8 = 11;
+const CELL_BONUS: u8 = 12;
+// How a snake's tick ended, as recorded in the move log.
+const MOVE_IDLE: u8 = 0; // already dead, or no legal direction and died in place
+const MOVE_MOVED: u8 = 1;
+const MOVE_CRASHED: u8 = 2; // died stepping off the board or into a body
+const MOVE_MOVED_THEN_DIED: u8 = 3; // moved, then caught by the shrinking wall
+const INITIAL_SNAKE_LEN: u16 = 3;
+// Seeded spawns (rules V2+) need this many open cells ahead of the head, and no
+// initial food within this many steps of a head.
+const SPAWN_CLEAR_AHEAD: u16 = 2;
+const SPAWN_FOOD_CLEARANCE: u16 = 2;
+// Re-rolls of an initial food item that lands too close to a spawn before the
+// last roll is kept anyway.
+const SPAWN_FOOD_TRIES: usize = 16;
+const MAX_FOOD: usize = 4;
+const GOLDEN_POINTS: u32 = 3;
+const BONUS_POINTS: u32 = 2;
+// Eating poison costs a point and this many tail segments; the head always stays.
+const POISON_SHRINK: u16 = 2;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
+// Obstacle layouts as wall rectangles `(x, y, width, height)` drawn on the left
+// half of the board and mirrored onto the right half, so both spawns face the
+// same map. Coordinates are for a BOARD_SIZE board; larger boards centre the
+// layout, smaller ones only allow the open map. Ids are part of the protocol:
+// append new layouts, never reorder.
+const MAP_LAYOUTS: [&[(i16, i16, i16, i16)]; 4] = [
+    // 0: open
+    &[],
+    // 1: pillars
+    &[(4, 4, 2, 2), (4, 14, 2, 2), (8, 8, 2, 2)],
+    // 2: corridors
+    &[(6, 2, 1, 6), (6, 12, 1, 6)],
+    // 3: rooms, one per spawn, each with a door towards the centre
+    &[(1, 6, 7, 1), (1, 13, 7, 1), (8, 6, 1, 3), (8, 11, 1, 3)],
+];
+// `RoundSetup.map_id` value that picks a layout from the round seed instead.
+const MAP_FROM_SEED: u8 = u8::MAX;
+
+// Cells a single flood fill may expand. Strategies run at most one fill per
+// candidate move, so this caps the per-move search cost of execute_move.
+const FLOOD_FILL_LIMIT: usize = 64;
+// Cells a shortest-path search (`first_step_towards`) may queue from rules
+// version 5. TailFollower runs up to three searches per move.
+const PATH_SEARCH_LIMIT: usize = 256;
+
+// Pacing defaults; each round carries its own copy in `RoundRules`.
+const MAX_MOVES: u32 = 300;
 const FOOD_MIRROR_UNTIL_MOVE: u32 = 120;
 const SHRINK_START_MOVE: u32 = 150;
 const SHRINK_INTERVAL: u32 = 30;
+// Winner rules for rounds whose `RoundRules.tie_breaks` is left empty.
+const DEFAULT_TIE_BREAKS: [TieBreak; 3] = [TieBreak::Survival, TieBreak::Score, TieBreak::DeathMove];
+// One slot per `TieBreak`.
+const MAX_TIE_BREAKS: usize = 6;
 
+// Protocol-wide bet bounds; each template picks its limits inside them.
 const MIN_BET_LAMPORTS: u64 = 10_000_000; // 0.01 SOL
 const MAX_BET_LAMPORTS: u64 = 1_000_000_000; // 1 SOL
 
 const CONFIG_SPACE: usize = 80;
 const HOUSE_SPACE: usize = 8;
-const ROUND_SPACE: usize = 1200;
+// Round account size for a `board_size` x `board_size` game: the zero-copy
+// header, then both packed boards (half a byte per cell) and both body rings
+// (2 bytes per cell); see `RoundData`.
+const fn round_space(board_size: u8) -> usize {
+    let cells = board_size as usize * board_size as usize;
+    std::mem::size_of::<Round>() + 2 * packed_board_len(cells) + 2 * 2 * cells
+}
+// Move log size for a round of up to `max_moves` ticks with `food_count` items
+// per list: the header, then room for the genesis entry and a longest entry for
+// every tick, so the log cannot fill up.
+const fn move_log_space(max_moves: u32, food_count: u8) -> usize {
+    std::mem::size_of::<MoveLog>() + (1 + max_moves as usize) * log_entry_len(food_count)
+}
+// Most ticks a round with `food_count` items per list may run: as many as the
+// largest move log create_round can allocate has room for.
+const fn max_logged_moves(food_count: u8) -> u32 {
+    ((MAX_CPI_ACCOUNT_SPACE - 8 - std::mem::size_of::<MoveLog>()) / log_entry_len(food_count) - 1) as u32
+}
+// Longest log entry of a round with `food_count` items per list: 2 bytes plus 3
+// per food placement, and a tick places every slot of both lists at most
+// twice; see `FoodJournal`.
+const fn log_entry_len(food_count: u8) -> usize {
+    2 + 3 * 2 * 2 * food_count as usize
+}
+const MAX_LOG_ENTRY_LEN: usize = log_entry_len(MAX_FOOD as u8);
+const MAX_TICK_PLACEMENTS: usize = 2 * 2 * MAX_FOOD;
+// Largest account create_round can allocate: accounts created through a CPI
+// are capped at 10 KiB, which bounds the moves a round may run.
+const MAX_CPI_ACCOUNT_SPACE: usize = 10_240;
 const BET_SPACE: usize = 64;
+const ROUND_SETUP_SPACE: usize = 1 + 1 // alpha_strategy, beta_strategy
+    + 1 + 1 // mode, food_count
+    + 3 + 2 // food_odds
+    + 1 // map_id
+    + 4 * 4 + 1 + 1 + MAX_TIE_BREAKS; // rules
+const TEMPLATE_SPACE: usize = 4 // template_id
+    + ROUND_SETUP_SPACE
+    + 8 + 8 + 8 // duration, min_bet, max_bet
+    + 1 // retired
+    + 1; // bump
 const VAULT_SPACE: usize = 16;
 
+// Seconds after close_betting for the seed to arrive before a round is voidable.
+const SEED_DEADLINE_SECS: i64 = 120;
+// Slots from close_betting to `Round.seed_slot`, whose hash seeds commit-reveal
+// and bettor-entropy rounds, so that hash does not exist yet when betting closes.
+const SEED_SLOT_DELAY: u64 = 4;
+
 #[ephemeral]
 #[program]
 pub mod magic_bet {
@@ -76,42 +230,102 @@ pub mod magic_bet {
         Ok(())
     }
 
-    pub fn create_round(ctx: Context<CreateRound>, round_id: u64, duration: i64) -> Result<()> {
-        require!(duration > 0, MagicBetError::InvalidDuration);
+    pub fn create_template(
+        ctx: Context<CreateTemplate>,
+        template_id: u32,
+        params: TemplateParams,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.admin,
+            ctx.accounts.admin.key(),
+            MagicBetError::Unauthorized
+        );
+        params.validate()?;
+
+        let template = &mut ctx.accounts.template;
+        template.template_id = template_id;
+        template.apply(&params);
+        template.retired = false;
+        template.bump = ctx.bumps.template;
+        Ok(())
+    }
+
+    /// Changes apply to rounds created afterwards; existing rounds keep the
+    /// copy they took at create_round.
+    pub fn update_template(
+        ctx: Context<ManageTemplate>,
+        _template_id: u32,
+        params: TemplateParams,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.admin,
+            ctx.accounts.admin.key(),
+            MagicBetError::Unauthorized
+        );
+        require!(!ctx.accounts.template.retired, MagicBetError::TemplateRetired);
+        params.validate()?;
+
+        ctx.accounts.template.apply(&params);
+        Ok(())
+    }
+
+    pub fn retire_template(ctx: Context<ManageTemplate>, _template_id: u32) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.admin,
+            ctx.accounts.admin.key(),
+            MagicBetError::Unauthorized
+        );
+        ctx.accounts.template.retired = true;
+        Ok(())
+    }
+
+    pub fn create_round(
+        ctx: Context<CreateRound>,
+        round_id: u64,
+        template_id: u32,
+        seed_source: SeedSource,
+    ) -> Result<()> {
+        let template = &ctx.accounts.template;
+        require!(!template.retired, MagicBetError::TemplateRetired);
 
         let config = &mut ctx.accounts.config;
         require_admin_or_agent(config, ctx.accounts.signer.key())?;
         require!(round_id == config.round_id, MagicBetError::InvalidRoundId);
+        if let SeedSource::Vrf { oracle } = seed_source {
+            require!(VRF_ROUNDS_SUPPORTED, MagicBetError::VrfUnsupported);
+            require_keys_eq!(oracle, VRF_ORACLE_PROGRAM_ID, MagicBetError::InvalidOracle);
+        }
 
-        let round = &mut ctx.accounts.round;
+        // The new account is zeroed: scores, pools, heads, bodies and both
+        // boards (all CELL_EMPTY) start out blank.
+        let mut round = ctx.accounts.round.load_init()?;
         round.round_id = round_id;
-        round.status = RoundStatus::Active;
-        round.winner = None;
-        round.alpha_board = [CELL_EMPTY; BOARD_CELLS];
-        round.beta_board = [CELL_EMPTY; BOARD_CELLS];
-        round.alpha_seed = make_seed(round_id, 0xA11A_A11A_A11A_A11A);
-        round.beta_seed = make_seed(round_id, 0xB37A_B37A_B37A_B37A);
-        round.alpha_score = 0;
-        round.beta_score = 0;
-        round.alpha_alive = true;
-        round.beta_alive = true;
-        round.move_count = 0;
-        round.alpha_pool = 0;
-        round.beta_pool = 0;
+        round.status = RoundStatus::Active as u8;
+        round.alpha_alive = 1;
+        round.beta_alive = 1;
         round.start_time = Clock::get()?.unix_timestamp;
-        round.end_time = None;
-        round.duration = duration;
-        round.alpha_head = 0;
-        round.beta_head = 0;
-        round.alpha_food = 0;
-        round.beta_food = 0;
-        round.alpha_dir = Direction::Right;
-        round.beta_dir = Direction::Left;
-        round.alpha_death_move = None;
-        round.beta_death_move = None;
+        round.duration = template.duration;
+        round.alpha_dir = Direction::Right as u8;
+        round.beta_dir = Direction::Left as u8;
+        round.set_seed_source(seed_source);
+        round.set_setup(&template.setup);
+        round.template_id = template_id;
+        round.rules_version = RulesVersion::CURRENT as u16;
+        round.min_bet = template.min_bet;
+        round.max_bet = template.max_bet;
         round.bump = ctx.bumps.round;
 
-        initialize_round_state(round)?;
+        let mut move_log = ctx.accounts.move_log.load_init()?;
+        move_log.round_id = round_id;
+        move_log.board_size = round.board_size;
+        move_log.mode = round.mode;
+        move_log.food_count = round.food_count;
+        move_log.toroidal = round.toroidal;
+        move_log.rules_version = round.rules_version;
+        move_log.bump = ctx.bumps.move_log;
+
+        // Boards stay empty until the seed is fulfilled or revealed after betting
+        // closes, so the outcome cannot be simulated while bets are open.
 
         let vault = &mut ctx.accounts.vault;
         vault.round_id = round_id;
@@ -129,10 +343,14 @@ pub mod magic_bet {
     pub fn delegate_round(ctx: Context<DelegateRound>, round_id: u64) -> Result<()> {
         let config = &ctx.accounts.config;
         require_admin_or_agent(config, ctx.accounts.signer.key())?;
-        require!(
-            ctx.accounts.round.status == RoundStatus::InProgress,
-            MagicBetError::RoundNotInProgress
-        );
+        {
+            let round = ctx.accounts.round.load()?;
+            require!(
+                round.status == RoundStatus::InProgress as u8,
+                MagicBetError::RoundNotInProgress
+            );
+            require!(round.seeded != 0, MagicBetError::RoundNotSeeded);
+        }
 
         let validator = ctx.remaining_accounts.first().map(|acc| acc.key());
         let round_id_bytes = round_id.to_le_bytes();
@@ -146,6 +364,14 @@ pub mod magic_bet {
                 ..Default::default()
             },
         )?;
+        ctx.accounts.delegate_move_log(
+            &ctx.accounts.signer,
+            &[MOVE_LOG_SEED, &round_id_bytes],
+            DelegateConfig {
+                validator,
+                ..Default::default()
+            },
+        )?;
         Ok(())
     }
 
@@ -154,13 +380,18 @@ pub mod magic_bet {
         round_id: u64,
         choice: AIChoice,
         amount: u64,
+        entropy: Option<[u8; 32]>,
     ) -> Result<()> {
-        require!(amount >= MIN_BET_LAMPORTS, MagicBetError::BetAmountTooLow);
-        require!(amount <= MAX_BET_LAMPORTS, MagicBetError::BetAmountTooHigh);
-        require!(
-            ctx.accounts.round.status == RoundStatus::Active,
-            MagicBetError::RoundNotActive
-        );
+        let (alpha_pool, beta_pool) = {
+            let round = ctx.accounts.round.load()?;
+            require!(amount >= round.min_bet, MagicBetError::BetAmountTooLow);
+            require!(amount <= round.max_bet, MagicBetError::BetAmountTooHigh);
+            require!(
+                round.status == RoundStatus::Active as u8,
+                MagicBetError::RoundNotActive
+            );
+            (round.alpha_pool, round.beta_pool)
+        };
 
         let bet = &mut ctx.accounts.bet;
         let is_new_bet = bet.user == Pubkey::default();
@@ -178,11 +409,8 @@ pub mod magic_bet {
         let potential_payout = existing_amount
             .checked_add(amount)
             .ok_or(MagicBetError::ArithmeticOverflow)?;
-        let existing_exposure = ctx
-            .accounts
-            .round
-            .alpha_pool
-            .checked_add(ctx.accounts.round.beta_pool)
+        let existing_exposure = alpha_pool
+            .checked_add(beta_pool)
             .ok_or(MagicBetError::ArithmeticOverflow)?
             .checked_mul(2)
             .ok_or(MagicBetError::ArithmeticOverflow)?;
@@ -222,19 +450,16 @@ pub mod magic_bet {
                 .ok_or(MagicBetError::ArithmeticOverflow)?;
         }
 
+        let mut round = ctx.accounts.round.load_mut()?;
         match choice {
             AIChoice::Alpha => {
-                ctx.accounts.round.alpha_pool = ctx
-                    .accounts
-                    .round
+                round.alpha_pool = round
                     .alpha_pool
                     .checked_add(amount)
                     .ok_or(MagicBetError::ArithmeticOverflow)?;
             }
             AIChoice::Beta => {
-                ctx.accounts.round.beta_pool = ctx
-                    .accounts
-                    .round
+                round.beta_pool = round
                     .beta_pool
                     .checked_add(amount)
                     .ok_or(MagicBetError::ArithmeticOverflow)?;
@@ -242,76 +467,236 @@ pub mod magic_bet {
             AIChoice::Draw => return err!(MagicBetError::InvalidBetChoice),
         }
 
+        if let Some(entropy) = entropy {
+            round.entropy_accumulator = hashv(&[
+                round.entropy_accumulator.as_ref(),
+                ctx.accounts.user.key().as_ref(),
+                entropy.as_ref(),
+            ])
+            .to_bytes();
+        }
+
         Ok(())
     }
 
     pub fn close_betting(ctx: Context<CloseBetting>, _round_id: u64) -> Result<()> {
         let config = &ctx.accounts.config;
         require_admin_or_agent(config, ctx.accounts.signer.key())?;
-        require!(
-            ctx.accounts.round.status == RoundStatus::Active,
-            MagicBetError::RoundNotActive
-        );
 
-        ctx.accounts.round.status = RoundStatus::InProgress;
+        let clock = Clock::get()?;
+        let seed_deadline = clock
+            .unix_timestamp
+            .checked_add(SEED_DEADLINE_SECS)
+            .ok_or(MagicBetError::ArithmeticOverflow)?;
+        let seed_slot = clock
+            .slot
+            .checked_add(SEED_SLOT_DELAY)
+            .ok_or(MagicBetError::ArithmeticOverflow)?;
+        let seed_source = {
+            let mut round = ctx.accounts.round.load_mut()?;
+            require!(
+                round.status == RoundStatus::Active as u8,
+                MagicBetError::RoundNotActive
+            );
+            round.status = RoundStatus::InProgress as u8;
+            round.seed_deadline = seed_deadline;
+            round.seed_slot = seed_slot;
+            round.seed_source()
+        };
+
+        // Commit-reveal and bettor-entropy rounds are seeded once the seed slot
+        // has passed, by reveal_seed and seed_from_entropy.
+        if let SeedSource::Vrf { oracle } = seed_source {
+            request_round_randomness(&ctx, oracle)?;
+        }
         Ok(())
     }
 
-    pub fn execute_move(ctx: Context<ExecuteMove>, _round_id: u64) -> Result<()> {
+    pub fn fulfill_round_seed(ctx: Context<FulfillRoundSeed>, randomness: [u8; 32]) -> Result<()> {
+        let vrf_identity = ctx.accounts.vrf_identity.key();
+        with_round_data(&ctx.accounts.round, |data| {
+            require!(
+                matches!(data.round.seed_source(), SeedSource::Vrf { .. }),
+                MagicBetError::InvalidSeedSource
+            );
+            let (expected_identity, _) =
+                Pubkey::find_program_address(&[IDENTITY_SEED], &VRF_ORACLE_PROGRAM_ID);
+            require_keys_eq!(vrf_identity, expected_identity, MagicBetError::InvalidOracle);
+            require!(
+                data.round.status == RoundStatus::InProgress as u8,
+                MagicBetError::RoundNotInProgress
+            );
+            require!(data.round.seeded == 0, MagicBetError::RoundAlreadySeeded);
+            // A late callback loses to void_round rather than racing it.
+            let now = Clock::get()?.unix_timestamp;
+            require!(now <= data.round.seed_deadline, MagicBetError::SeedDeadlinePassed);
+
+            seed_round(data, &randomness)
+        })
+    }
+
+    pub fn reveal_seed(ctx: Context<RevealSeed>, _round_id: u64, secret: [u8; 32]) -> Result<()> {
         let config = &ctx.accounts.config;
         require_admin_or_agent(config, ctx.accounts.signer.key())?;
 
-        let round = &mut ctx.accounts.round;
+        let slot_hashes = &ctx.accounts.slot_hashes;
+        with_round_data(&ctx.accounts.round, |data| {
+            let commitment = match data.round.seed_source() {
+                SeedSource::CommitReveal { commitment } => commitment,
+                _ => return err!(MagicBetError::InvalidSeedSource),
+            };
+            require!(
+                data.round.status == RoundStatus::InProgress as u8,
+                MagicBetError::RoundNotInProgress
+            );
+            require!(data.round.seeded == 0, MagicBetError::RoundAlreadySeeded);
+            require!(
+                hashv(&[secret.as_ref()]).to_bytes() == commitment,
+                MagicBetError::InvalidSeedReveal
+            );
+
+            let now = Clock::get()?.unix_timestamp;
+            let deadline = data.round.seed_deadline;
+            require!(deadline != 0, MagicBetError::RoundNotInProgress);
+            require!(now <= deadline, MagicBetError::SeedDeadlinePassed);
+
+            // The seed slot was fixed at close_betting, ahead of the chain, so the
+            // operator can neither pick a secret for a known hash nor pick the
+            // hash by choosing when to reveal.
+            let slot_hash = slot_hash_at(&slot_hashes.try_borrow_data()?, data.round.seed_slot)
+                .ok_or(MagicBetError::SeedSlotHashUnavailable)?;
+            let randomness = hashv(&[secret.as_ref(), slot_hash.as_ref()]).to_bytes();
+
+            seed_round(data, &randomness)
+        })
+    }
+
+    /// Seeds a bettor-entropy round from its accumulator and the hash of its
+    /// seed slot. Permissionless: both were fixed when betting closed, and the
+    /// slot hash did not exist yet, so no caller can steer the boards.
+    pub fn seed_from_entropy(ctx: Context<SeedFromEntropy>, _round_id: u64) -> Result<()> {
+        let slot_hashes = &ctx.accounts.slot_hashes;
+        with_round_data(&ctx.accounts.round, |data| {
+            require!(
+                data.round.seed_source() == SeedSource::BettorEntropy,
+                MagicBetError::InvalidSeedSource
+            );
+            require!(
+                data.round.status == RoundStatus::InProgress as u8,
+                MagicBetError::RoundNotInProgress
+            );
+            require!(data.round.seeded == 0, MagicBetError::RoundAlreadySeeded);
+            let now = Clock::get()?.unix_timestamp;
+            require!(now <= data.round.seed_deadline, MagicBetError::SeedDeadlinePassed);
+
+            let slot_hash = slot_hash_at(&slot_hashes.try_borrow_data()?, data.round.seed_slot)
+                .ok_or(MagicBetError::SeedSlotHashUnavailable)?;
+            seed_round(data, &slot_hash)
+        })
+    }
+
+    pub fn void_round(ctx: Context<VoidRound>, _round_id: u64) -> Result<()> {
+        let mut round = ctx.accounts.round.load_mut()?;
         require!(
-            round.status == RoundStatus::InProgress,
+            round.status == RoundStatus::InProgress as u8,
             MagicBetError::RoundNotInProgress
         );
-        require!(round.winner.is_none(), MagicBetError::RoundAlreadyResolved);
+        require!(round.seeded == 0, MagicBetError::RoundAlreadySeeded);
 
-        let move_number = round
-            .move_count
-            .checked_add(1)
-            .ok_or(MagicBetError::ArithmeticOverflow)?;
+        // Permissionless so bettors are never stuck waiting on the operator.
+        let now = Clock::get()?.unix_timestamp;
+        require!(round.seed_deadline != 0, MagicBetError::RoundNotInProgress);
+        require!(now > round.seed_deadline, MagicBetError::SeedDeadlineNotReached);
 
-        let mut alpha = SnakeRuntime::from_alpha(round);
-        let mut beta = SnakeRuntime::from_beta(round);
+        round.status = RoundStatus::Voided as u8;
+        round.end_time = now;
+        Ok(())
+    }
 
-        let alpha_direction = alpha.choose_aggressive_direction();
-        let beta_direction = beta.choose_aggressive_direction();
+    pub fn refund_bet(ctx: Context<RefundBet>, _round_id: u64, _user: Pubkey) -> Result<()> {
+        let mut round = ctx.accounts.round.load_mut()?;
+        require!(
+            round.status == RoundStatus::Voided as u8,
+            MagicBetError::RoundNotVoided
+        );
 
-        let alpha_ate = alpha.apply_move(alpha_direction, move_number);
-        let beta_ate = beta.apply_move(beta_direction, move_number);
+        let bet = &mut ctx.accounts.bet;
+        require!(!bet.claimed, MagicBetError::AlreadyClaimed);
 
-        if should_use_mirrored_food(move_number) {
-            if alpha_ate || beta_ate {
-                respawn_symmetric_food(&mut alpha, &mut beta)?;
-            }
-        } else {
-            if alpha_ate {
-                respawn_food_single(&mut alpha)?;
+        match bet.choice {
+            AIChoice::Alpha => {
+                round.alpha_pool = round
+                    .alpha_pool
+                    .checked_sub(bet.amount)
+                    .ok_or(MagicBetError::ArithmeticOverflow)?;
             }
-            if beta_ate {
-                respawn_food_single(&mut beta)?;
+            AIChoice::Beta => {
+                round.beta_pool = round
+                    .beta_pool
+                    .checked_sub(bet.amount)
+                    .ok_or(MagicBetError::ArithmeticOverflow)?;
             }
+            AIChoice::Draw => return err!(MagicBetError::InvalidBetChoice),
         }
 
-        apply_shrink_to_runtime(&mut alpha, move_number)?;
-        apply_shrink_to_runtime(&mut beta, move_number)?;
-        ensure_food_present(&mut alpha)?;
-        ensure_food_present(&mut beta)?;
+        // Vault is a program-owned PDA carrying data, same as the House payout path.
+        let vault_balance = ctx.accounts.vault.to_account_info().lamports();
+        let user_balance = ctx.accounts.user_account.lamports();
 
-        alpha.write_back_alpha(round);
-        beta.write_back_beta(round);
-        round.move_count = move_number;
+        let new_vault_balance = vault_balance
+            .checked_sub(bet.amount)
+            .ok_or(MagicBetError::ArithmeticOverflow)?;
+        let new_user_balance = user_balance
+            .checked_add(bet.amount)
+            .ok_or(MagicBetError::ArithmeticOverflow)?;
 
-        let force_resolution = round.move_count >= max_round_moves(round.duration);
-        if let Some(winner) = determine_winner(round, force_resolution) {
-            round.winner = Some(winner);
-        }
+        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? = new_vault_balance;
+        **ctx.accounts.user_account.try_borrow_mut_lamports()? = new_user_balance;
 
+        bet.claimed = true;
         Ok(())
     }
 
+    pub fn execute_move(ctx: Context<ExecuteMove>, _round_id: u64) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require_admin_or_agent(config, ctx.accounts.signer.key())?;
+
+        with_round_data(&ctx.accounts.round, |data| {
+            require!(
+                data.round.status == RoundStatus::InProgress as u8,
+                MagicBetError::RoundNotInProgress
+            );
+            require!(data.round.winner().is_none(), MagicBetError::RoundAlreadyResolved);
+
+            with_move_log(&ctx.accounts.move_log, |log| advance_round_logged(data, log))
+        })
+    }
+
+    /// Runs up to `count` ticks in one instruction, stopping early once the
+    /// round resolves or the compute budget left drops below
+    /// `TICK_COMPUTE_RESERVE`. Returns the number of ticks run (also as return
+    /// data).
+    pub fn execute_moves(ctx: Context<ExecuteMove>, _round_id: u64, count: u8) -> Result<u8> {
+        let config = &ctx.accounts.config;
+        require_admin_or_agent(config, ctx.accounts.signer.key())?;
+        require!(
+            (1..=MAX_MOVES_PER_BATCH).contains(&count),
+            MagicBetError::InvalidMoveCount
+        );
+
+        with_round_data(&ctx.accounts.round, |data| {
+            require!(
+                data.round.status == RoundStatus::InProgress as u8,
+                MagicBetError::RoundNotInProgress
+            );
+            require!(data.round.winner().is_none(), MagicBetError::RoundAlreadyResolved);
+
+            with_move_log(&ctx.accounts.move_log, |log| {
+                advance_round_by(data, log, count, sol_remaining_compute_units)
+            })
+        })
+    }
+
     pub fn settle_and_undelegate(
         ctx: Context<SettleAndUndelegate>,
         _round_id: u64,
@@ -319,27 +704,28 @@ pub mod magic_bet {
         let config = &ctx.accounts.config;
         require_admin_or_agent(config, ctx.accounts.payer.key())?;
 
-        let round = &mut ctx.accounts.round;
-        require!(
-            round.status == RoundStatus::InProgress,
-            MagicBetError::RoundNotInProgress
-        );
-
-        if round.winner.is_none() {
-            round.winner = determine_winner(round, true);
-        }
-        if round.winner.is_none() {
-            round.winner = Some(AIChoice::Draw);
-        }
+        {
+            let mut round = ctx.accounts.round.load_mut()?;
+            require!(
+                round.status == RoundStatus::InProgress as u8,
+                MagicBetError::RoundNotInProgress
+            );
 
-        round.status = RoundStatus::Settled;
-        round.end_time = Some(Clock::get()?.unix_timestamp);
+            if round.winner().is_none() {
+                let winner = determine_winner(&round, true).unwrap_or(AIChoice::Draw);
+                round.set_winner(winner);
+            }
 
-        round.exit(&crate::ID)?;
+            round.status = RoundStatus::Settled as u8;
+            round.end_time = Clock::get()?.unix_timestamp;
+        }
 
         commit_and_undelegate_accounts(
             &ctx.accounts.payer,
-            vec![&ctx.accounts.round.to_account_info()],
+            vec![
+                &ctx.accounts.round.to_account_info(),
+                &ctx.accounts.move_log.to_account_info(),
+            ],
             &ctx.accounts.magic_context,
             &ctx.accounts.magic_program,
         )?;
@@ -348,16 +734,14 @@ pub mod magic_bet {
     }
 
     pub fn claim_winnings(ctx: Context<ClaimWinnings>, _round_id: u64) -> Result<()> {
-        require!(
-            ctx.accounts.round.status == RoundStatus::Settled,
-            MagicBetError::RoundNotSettled
-        );
-
-        let winner = ctx
-            .accounts
-            .round
-            .winner
-            .ok_or(MagicBetError::RoundNotSettled)?;
+        let winner = {
+            let round = ctx.accounts.round.load()?;
+            require!(
+                round.status == RoundStatus::Settled as u8,
+                MagicBetError::RoundNotSettled
+            );
+            round.winner().ok_or(MagicBetError::RoundNotSettled)?
+        };
         require!(winner != AIChoice::Draw, MagicBetError::DrawNoPayout);
 
         let bet = &mut ctx.accounts.bet;
@@ -394,12 +778,17 @@ pub mod magic_bet {
     }
 
     pub fn close_bet(ctx: Context<CloseBet>, _round_id: u64, _user: Pubkey) -> Result<()> {
+        let round = ctx.accounts.round.load()?;
+        if round.status == RoundStatus::Voided as u8 {
+            require!(ctx.accounts.bet.claimed, MagicBetError::RefundsPending);
+            return Ok(());
+        }
         require!(
-            ctx.accounts.round.status == RoundStatus::Settled,
+            round.status == RoundStatus::Settled as u8,
             MagicBetError::RoundNotSettled
         );
 
-        if let Some(winner) = ctx.accounts.round.winner {
+        if let Some(winner) = round.winner() {
             if winner == ctx.accounts.bet.choice {
                 require!(ctx.accounts.bet.claimed, MagicBetError::UnclaimedWinningBet);
             }
@@ -425,12 +814,46 @@ pub mod magic_bet {
 
     pub fn sweep_vault(ctx: Context<SweepVault>, _round_id: u64) -> Result<()> {
         require_admin_or_agent(&ctx.accounts.config, ctx.accounts.signer.key())?;
+        let round = ctx.accounts.round.load()?;
+        if round.status == RoundStatus::Voided as u8 {
+            require!(
+                round.alpha_pool == 0 && round.beta_pool == 0,
+                MagicBetError::RefundsPending
+            );
+            return Ok(());
+        }
         require!(
-            ctx.accounts.round.status == RoundStatus::Settled,
+            round.status == RoundStatus::Settled as u8,
             MagicBetError::RoundNotSettled
         );
         Ok(())
     }
+
+    /// Moves a `round_v2` round, left behind when rounds went zero-copy under
+    /// `round_v3`, to the current layout and closes the old account. Settled
+    /// rounds keep their winner so winning bets can still be claimed; every
+    /// other round is voided so its bets can be refunded. Bets and vaults never
+    /// changed layout and need no migration.
+    pub fn migrate_legacy_round(ctx: Context<MigrateLegacyRound>, _round_id: u64) -> Result<()> {
+        require_admin_or_agent(&ctx.accounts.config, ctx.accounts.signer.key())?;
+
+        let legacy_round = &ctx.accounts.legacy_round;
+        let legacy = LegacyRound::parse(&legacy_round.try_borrow_data()?)?;
+        let mut round = ctx.accounts.round.load_init()?;
+        migrate_round(&mut round, &legacy, Clock::get()?.unix_timestamp);
+        round.bump = ctx.bumps.round;
+
+        let signer = ctx.accounts.signer.to_account_info();
+        let new_signer_balance = signer
+            .lamports()
+            .checked_add(legacy_round.lamports())
+            .ok_or(MagicBetError::ArithmeticOverflow)?;
+        **signer.try_borrow_mut_lamports()? = new_signer_balance;
+        **legacy_round.try_borrow_mut_lamports()? = 0;
+        legacy_round.assign(&system_program::ID);
+        legacy_round.resize(0)?;
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -452,20 +875,68 @@ pub struct DelegateAdmin<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(round_id: u64, _duration: i64)]
+#[instruction(template_id: u32)]
+pub struct CreateTemplate<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + TEMPLATE_SPACE,
+        seeds = [TEMPLATE_SEED, &template_id.to_le_bytes()],
+        bump
+    )]
+    pub template: Account<'info, RoundTemplate>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(template_id: u32)]
+pub struct ManageTemplate<'info> {
+    pub admin: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [TEMPLATE_SEED, &template_id.to_le_bytes()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, RoundTemplate>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64, template_id: u32)]
 pub struct CreateRound<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
     #[account(mut, seeds = [CONFIG_SEED], bump)]
     pub config: Account<'info, Config>,
+    #[account(
+        seeds = [TEMPLATE_SEED, &template_id.to_le_bytes()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, RoundTemplate>,
     #[account(
         init,
         payer = signer,
-        space = 8 + ROUND_SPACE,
+        space = 8 + round_space(template.setup.rules.board_size),
         seeds = [ROUND_SEED, &round_id.to_le_bytes()],
         bump
     )]
-    pub round: Account<'info, Round>,
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + move_log_space(
+            max_round_moves(&template.setup.rules, template.duration),
+            template.setup.food_count
+        ),
+        seeds = [MOVE_LOG_SEED, &round_id.to_le_bytes()],
+        bump
+    )]
+    pub move_log: AccountLoader<'info, MoveLog>,
     #[account(
         init,
         payer = signer,
@@ -484,8 +955,8 @@ pub struct DelegateRound<'info> {
     pub signer: Signer<'info>,
     #[account(seeds = [CONFIG_SEED], bump)]
     pub config: Account<'info, Config>,
-    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.bump)]
-    pub round: Account<'info, Round>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
     /// CHECK: Delegated round PDA; verified by seeds and bump constraints.
     #[account(
         mut,
@@ -494,6 +965,9 @@ pub struct DelegateRound<'info> {
         bump
     )]
     pub round_pda: AccountInfo<'info>,
+    /// CHECK: The round's move log PDA, delegated with it; verified by seeds.
+    #[account(mut, del, seeds = [MOVE_LOG_SEED, &round_id.to_le_bytes()], bump)]
+    pub move_log: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -503,8 +977,8 @@ pub struct PlaceBet<'info> {
     pub user: Signer<'info>,
     #[account(seeds = [CONFIG_SEED], bump)]
     pub config: Account<'info, Config>,
-    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.bump)]
-    pub round: Account<'info, Round>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
     #[account(mut, seeds = [VAULT_SEED, &round_id.to_le_bytes()], bump = vault.bump)]
     pub vault: Account<'info, Vault>,
     #[account(
@@ -523,11 +997,80 @@ pub struct PlaceBet<'info> {
 #[derive(Accounts)]
 #[instruction(round_id: u64)]
 pub struct CloseBetting<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
+    /// CHECK: Program signer PDA that authenticates the randomness request.
+    #[account(seeds = [IDENTITY_SEED], bump)]
+    pub program_identity: Option<AccountInfo<'info>>,
+    /// CHECK: Oracle program chosen at create_round; checked in the handler.
+    #[account(executable)]
+    pub oracle_program: Option<AccountInfo<'info>>,
+    /// CHECK: Request account created and validated by the oracle program.
+    #[account(mut)]
+    pub oracle_request: Option<AccountInfo<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FulfillRoundSeed<'info> {
+    pub vrf_identity: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ROUND_SEED, &round.load()?.round_id.to_le_bytes()],
+        bump = round.load()?.bump
+    )]
+    pub round: AccountLoader<'info, Round>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct RevealSeed<'info> {
     pub signer: Signer<'info>,
     #[account(seeds = [CONFIG_SEED], bump)]
     pub config: Account<'info, Config>,
-    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.bump)]
-    pub round: Account<'info, Round>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
+    /// CHECK: SlotHashes sysvar, read manually because it is too large to deserialize.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct SeedFromEntropy<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
+    /// CHECK: SlotHashes sysvar, read manually because it is too large to deserialize.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct VoidRound<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64, user: Pubkey)]
+pub struct RefundBet<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
+    #[account(mut, seeds = [BET_SEED, &round_id.to_le_bytes(), user.as_ref()], bump = bet.bump)]
+    pub bet: Account<'info, Bet>,
+    #[account(mut, seeds = [VAULT_SEED, &round_id.to_le_bytes()], bump = vault.bump)]
+    pub vault: Account<'info, Vault>,
+    /// CHECK: Receives the refunded stake; must be the bettor.
+    #[account(mut, address = user)]
+    pub user_account: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -536,8 +1079,14 @@ pub struct ExecuteMove<'info> {
     pub signer: Signer<'info>,
     #[account(seeds = [CONFIG_SEED], bump)]
     pub config: Account<'info, Config>,
-    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.bump)]
-    pub round: Account<'info, Round>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        seeds = [MOVE_LOG_SEED, &round_id.to_le_bytes()],
+        bump = move_log.load()?.bump
+    )]
+    pub move_log: AccountLoader<'info, MoveLog>,
 }
 
 #[commit]
@@ -548,8 +1097,14 @@ pub struct SettleAndUndelegate<'info> {
     pub payer: Signer<'info>,
     #[account(seeds = [CONFIG_SEED], bump)]
     pub config: Account<'info, Config>,
-    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.bump)]
-    pub round: Account<'info, Round>,
+    #[account(mut, seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        seeds = [MOVE_LOG_SEED, &round_id.to_le_bytes()],
+        bump = move_log.load()?.bump
+    )]
+    pub move_log: AccountLoader<'info, MoveLog>,
 }
 
 #[derive(Accounts)]
@@ -559,8 +1114,8 @@ pub struct ClaimWinnings<'info> {
     pub user: Signer<'info>,
     #[account(seeds = [CONFIG_SEED], bump)]
     pub config: Account<'info, Config>,
-    #[account(seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.bump)]
-    pub round: Account<'info, Round>,
+    #[account(seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
     #[account(mut, seeds = [BET_SEED, &round_id.to_le_bytes(), user.key().as_ref()], bump = bet.bump)]
     pub bet: Account<'info, Bet>,
     #[account(mut, seeds = [HOUSE_SEED], bump = house.bump)]
@@ -572,8 +1127,8 @@ pub struct ClaimWinnings<'info> {
 #[instruction(round_id: u64, user: Pubkey)]
 pub struct CloseBet<'info> {
     pub signer: Signer<'info>,
-    #[account(seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.bump)]
-    pub round: Account<'info, Round>,
+    #[account(seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
     #[account(
         mut,
         seeds = [BET_SEED, &round_id.to_le_bytes(), user.as_ref()],
@@ -604,8 +1159,8 @@ pub struct SweepVault<'info> {
     pub signer: Signer<'info>,
     #[account(seeds = [CONFIG_SEED], bump)]
     pub config: Account<'info, Config>,
-    #[account(seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.bump)]
-    pub round: Account<'info, Round>,
+    #[account(seeds = [ROUND_SEED, &round_id.to_le_bytes()], bump = round.load()?.bump)]
+    pub round: AccountLoader<'info, Round>,
     #[account(mut, seeds = [HOUSE_SEED], bump = house.bump)]
     pub house: Account<'info, House>,
     #[account(
@@ -617,6 +1172,33 @@ pub struct SweepVault<'info> {
     pub vault: Account<'info, Vault>,
 }
 
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct MigrateLegacyRound<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: Legacy round PDA, read manually because its layout is gone;
+    /// rounds still delegated to the ER are not program-owned and are refused.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [LEGACY_ROUND_SEED, &round_id.to_le_bytes()],
+        bump
+    )]
+    pub legacy_round: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + round_space(BOARD_SIZE as u8),
+        seeds = [ROUND_SEED, &round_id.to_le_bytes()],
+        bump
+    )]
+    pub round: AccountLoader<'info, Round>,
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct Config {
     pub admin: Pubkey,
@@ -638,43 +1220,560 @@ pub struct Vault {
     pub bump: u8,
 }
 
-#[account]
+/// Zero-copy round header. The boards and body rings follow it in the account
+/// data (see `RoundData`). Fields are ordered by alignment so the layout has no
+/// padding, and enums, options and flags are stored as plain integers.
+#[account(zero_copy)]
 pub struct Round {
     pub round_id: u64,
-    pub status: RoundStatus,
-    pub winner: Option<AIChoice>,
-
-    pub alpha_board: [u8; BOARD_CELLS],
-    pub beta_board: [u8; BOARD_CELLS],
-
+    /// Food streams of each snake; see `Rng`.
     pub alpha_seed: u64,
     pub beta_seed: u64,
-    pub alpha_score: u32,
-    pub beta_score: u32,
-    pub alpha_alive: bool,
-    pub beta_alive: bool,
-    pub move_count: u32,
-
+    /// Food stream for placements made on both boards at once (mirrored and
+    /// shared food); unused before `RulesVersion::V3`.
+    pub food_seed: u64,
+    /// Orders the toroidal shrinking wall's blocks, split off the round seed
+    /// at seeding; unused before `RulesVersion::V7`. See `Round::scatter_seed`.
+    pub shrink_seed: u64,
     pub alpha_pool: u64,
     pub beta_pool: u64,
-
     pub start_time: i64,
-    pub end_time: Option<i64>,
+    /// 0 until the round is settled or voided.
+    pub end_time: i64,
     pub duration: i64,
+    /// 0 until close_betting.
+    pub seed_deadline: i64,
+    /// Slot whose hash (or the next block's, if the slot is skipped) seeds a
+    /// commit-reveal or bettor-entropy round; set at close_betting, ahead of
+    /// the current slot.
+    pub seed_slot: u64,
+    /// Bet limits set by the template.
+    pub min_bet: u64,
+    pub max_bet: u64,
+
+    pub alpha_score: u32,
+    pub beta_score: u32,
+    pub move_count: u32,
+    /// Move each snake died on; 0 while it is alive.
+    pub alpha_death_move: u32,
+    pub beta_death_move: u32,
+    /// Move each snake last ate scoring food on; 0 before its first meal.
+    pub alpha_last_meal: u32,
+    pub beta_last_meal: u32,
+    /// Template the round was created from.
+    pub template_id: u32,
+    /// `RoundRules` without `board_size`; see `Round::rules`.
+    pub max_moves: u32,
+    pub food_mirror_until: u32,
+    pub shrink_start: u32,
+    pub shrink_interval: u32,
+    /// Move at which the bonus food in each slot expires; 0 for other kinds.
+    pub alpha_food_expiry: [u32; MAX_FOOD],
+    pub beta_food_expiry: [u32; MAX_FOOD],
 
     pub alpha_head: u16,
     pub beta_head: u16,
-    pub alpha_food: u16,
-    pub beta_food: u16,
-    pub alpha_dir: Direction,
-    pub beta_dir: Direction,
-
-    pub alpha_death_move: Option<u32>,
-    pub beta_death_move: Option<u32>,
+    /// Food cells; only the first `food_count` slots are in play.
+    pub alpha_food: [u16; MAX_FOOD],
+    pub beta_food: [u16; MAX_FOOD],
+    /// Body rings hold `len` segments starting at `tail`, head last.
+    pub alpha_tail: u16,
+    pub alpha_len: u16,
+    pub beta_tail: u16,
+    pub beta_len: u16,
+    pub bonus_ttl: u16,
+    /// `RulesVersion` the round was created under; every tick runs it.
+    pub rules_version: u16,
+    /// `RoundRules::toroidal` as 0 or 1.
+    pub toroidal: u8,
+    /// `RoundRules::tie_breaks`; see `Round::tie_breaks`.
+    pub tie_breaks: [u8; MAX_TIE_BREAKS],
+    /// Zero; spare bytes that keep the header 8-byte aligned.
+    pub reserved: [u8; 7],
+
+    /// Oracle (`Vrf`) or commitment (`CommitReveal`); see `Round::seed_source`.
+    pub seed_param: [u8; 32],
+    pub entropy_accumulator: [u8; 32],
+    /// Rolling hash over every frame since the boards were laid out; see
+    /// `chain_state_hash`.
+    pub state_hash: [u8; 32],
+
+    /// `RoundStatus` discriminant.
+    pub status: u8,
+    /// 0 until resolved, then 1 + the `AIChoice` discriminant.
+    pub winner: u8,
+    /// `SeedSource` discriminant.
+    pub seed_source: u8,
+    pub seeded: u8,
+    pub alpha_alive: u8,
+    pub beta_alive: u8,
+    /// `Direction` discriminants.
+    pub alpha_dir: u8,
+    pub beta_dir: u8,
+    pub alpha_strategy: u8,
+    pub beta_strategy: u8,
+    /// `RoundMode` discriminant.
+    pub mode: u8,
+    pub food_count: u8,
+    /// `FoodOdds` percentages; see `Round::food_odds`.
+    pub golden_pct: u8,
+    pub poison_pct: u8,
+    pub bonus_pct: u8,
+    /// Index into `MAP_LAYOUTS`; `MAP_FROM_SEED` until the round is seeded.
+    pub map_id: u8,
+    /// Board width and height; the account is sized for it at create_round.
+    pub board_size: u8,
+    pub bump: u8,
+}
+
+impl Round {
+    fn set_setup(&mut self, setup: &RoundSetup) {
+        self.alpha_strategy = setup.alpha_strategy;
+        self.beta_strategy = setup.beta_strategy;
+        self.mode = setup.mode as u8;
+        self.food_count = setup.food_count;
+        self.set_food_odds(&setup.food_odds);
+        self.map_id = setup.map_id;
+        self.set_rules(&setup.rules);
+    }
+
+    fn rules(&self) -> RoundRules {
+        RoundRules {
+            max_moves: self.max_moves,
+            food_mirror_until: self.food_mirror_until,
+            shrink_start: self.shrink_start,
+            shrink_interval: self.shrink_interval,
+            board_size: self.board_size,
+            toroidal: self.toroidal != 0,
+            tie_breaks: self.tie_breaks,
+        }
+    }
+
+    fn set_rules(&mut self, rules: &RoundRules) {
+        self.max_moves = rules.max_moves;
+        self.food_mirror_until = rules.food_mirror_until;
+        self.shrink_start = rules.shrink_start;
+        self.shrink_interval = rules.shrink_interval;
+        self.board_size = rules.board_size;
+        self.toroidal = rules.toroidal as u8;
+        self.tie_breaks = rules.tie_breaks;
+    }
+
+    // Criteria `determine_winner` applies, in order; `DEFAULT_TIE_BREAKS`
+    // when the round set none.
+    fn tie_breaks(&self) -> impl Iterator<Item = TieBreak> + '_ {
+        let default: &[TieBreak] = match TieBreak::from_u8(self.tie_breaks[0]) {
+            Some(_) => &[],
+            None => &DEFAULT_TIE_BREAKS,
+        };
+        self.tie_breaks.iter().map_while(|id| TieBreak::from_u8(*id)).chain(default.iter().copied())
+    }
+
+    fn food_odds(&self) -> FoodOdds {
+        FoodOdds {
+            golden_pct: self.golden_pct,
+            poison_pct: self.poison_pct,
+            bonus_pct: self.bonus_pct,
+            bonus_ttl: self.bonus_ttl,
+        }
+    }
+
+    fn set_food_odds(&mut self, odds: &FoodOdds) {
+        self.golden_pct = odds.golden_pct;
+        self.poison_pct = odds.poison_pct;
+        self.bonus_pct = odds.bonus_pct;
+        self.bonus_ttl = odds.bonus_ttl;
+    }
+
+    fn mode(&self) -> RoundMode {
+        if self.mode == RoundMode::Shared as u8 {
+            RoundMode::Shared
+        } else {
+            RoundMode::Separate
+        }
+    }
+
+    fn rules_version(&self) -> Result<RulesVersion> {
+        RulesVersion::from_u16(self.rules_version).ok_or(MagicBetError::UnknownRulesVersion.into())
+    }
+
+    fn uses_lcg(&self) -> bool {
+        self.rules_version < RulesVersion::V3 as u16
+    }
+
+    fn places_reachable_food(&self) -> bool {
+        self.rules_version >= RulesVersion::V4 as u16
+    }
+
+    fn caps_path_search(&self) -> bool {
+        self.rules_version >= RulesVersion::V5 as u16
+    }
+
+    fn keeps_tail_on_death(&self) -> bool {
+        self.rules_version >= RulesVersion::V6 as u16
+    }
+
+    // Seed of the toroidal wall's block pattern. Rounds before V7 hashed the
+    // round id, which anyone could read before betting closed.
+    fn scatter_seed(&self) -> u64 {
+        if self.rules_version >= RulesVersion::V7 as u16 {
+            self.shrink_seed
+        } else {
+            self.round_id
+        }
+    }
+
+    fn winner(&self) -> Option<AIChoice> {
+        match self.winner {
+            1 => Some(AIChoice::Alpha),
+            2 => Some(AIChoice::Beta),
+            3 => Some(AIChoice::Draw),
+            _ => None,
+        }
+    }
+
+    fn set_winner(&mut self, winner: AIChoice) {
+        self.winner = winner as u8 + 1;
+    }
+
+    fn seed_source(&self) -> SeedSource {
+        match self.seed_source {
+            0 => SeedSource::Vrf {
+                oracle: Pubkey::new_from_array(self.seed_param),
+            },
+            1 => SeedSource::CommitReveal {
+                commitment: self.seed_param,
+            },
+            _ => SeedSource::BettorEntropy,
+        }
+    }
+
+    fn set_seed_source(&mut self, source: SeedSource) {
+        (self.seed_source, self.seed_param) = match source {
+            SeedSource::Vrf { oracle } => (0, oracle.to_bytes()),
+            SeedSource::CommitReveal { commitment } => (1, commitment),
+            SeedSource::BettorEntropy => (2, [0; 32]),
+        };
+    }
+}
+
+/// A round account split into its header and the regions that follow it:
+/// alpha board, beta board (packed, see `Board`), then alpha and beta body
+/// rings (little-endian u16 per cell). Engine code works on these slices in
+/// place, so a move never copies the boards.
+struct RoundData<'a> {
+    round: &'a mut Round,
+    alpha_board: Board<'a>,
+    beta_board: Board<'a>,
+    alpha_body: &'a mut [u16],
+    beta_body: &'a mut [u16],
+}
+
+impl<'a> RoundData<'a> {
+    /// `data` is the whole account, discriminator included; the loader has
+    /// already checked the owner and discriminator.
+    fn from_account_data(data: &'a mut [u8]) -> Result<Self> {
+        let header_len = std::mem::size_of::<Round>();
+        require!(data.len() >= 8 + header_len, ErrorCode::AccountDidNotDeserialize);
+        let (header, rest) = data[8..].split_at_mut(header_len);
+        let round: &mut Round = bytemuck::from_bytes_mut(header);
+        let cells = board_cells(round.board_size);
+        require!(
+            rest.len() >= round_space(round.board_size) - header_len,
+            ErrorCode::AccountDidNotDeserialize
+        );
+        let (alpha_board, rest) = rest.split_at_mut(packed_board_len(cells));
+        let (beta_board, rest) = rest.split_at_mut(packed_board_len(cells));
+        let (alpha_body, rest) = rest.split_at_mut(2 * cells);
+        let beta_body = &mut rest[..2 * cells];
+        Ok(Self {
+            round,
+            alpha_board: Board::new(alpha_board, cells),
+            beta_board: Board::new(beta_board, cells),
+            alpha_body: bytemuck::cast_slice_mut(alpha_body),
+            beta_body: bytemuck::cast_slice_mut(beta_body),
+        })
+    }
+}
+
+/// One board, row-major, two cells per byte: even cells in the low nibble and
+/// odd cells in the high nibble. Every `CELL_*` value fits in 4 bits; with an
+/// odd cell count the last high nibble is unused.
+struct Board<'a> {
+    bytes: &'a mut [u8],
+    cells: usize,
+}
+
+impl<'a> Board<'a> {
+    fn new(bytes: &'a mut [u8], cells: usize) -> Self {
+        Self { bytes, cells }
+    }
+
+    fn reborrow(&mut self) -> Board<'_> {
+        Board::new(self.bytes, self.cells)
+    }
+
+    fn get(&self, index: u16) -> u8 {
+        packed_cell(self.bytes, index)
+    }
 
+    fn set(&mut self, index: u16, value: u8) {
+        let shift = 4 * (index % 2);
+        let byte = &mut self.bytes[index as usize / 2];
+        *byte = (*byte & !(0x0F << shift)) | (value << shift);
+    }
+
+    fn len(&self) -> usize {
+        self.cells
+    }
+
+    fn fill(&mut self, value: u8) {
+        self.bytes.fill(value | (value << 4));
+    }
+
+    fn copy_from(&mut self, other: &Board) {
+        self.bytes.copy_from_slice(other.bytes);
+    }
+}
+
+const fn packed_board_len(cells: usize) -> usize {
+    cells.div_ceil(2)
+}
+
+fn packed_cell(bytes: &[u8], index: u16) -> u8 {
+    (bytes[index as usize / 2] >> (4 * (index % 2))) & 0x0F
+}
+
+// Runs `f` on a round's header and boards, borrowing the account data for the
+// duration of the call.
+fn with_round_data<T>(
+    round: &AccountLoader<Round>,
+    f: impl FnOnce(&mut RoundData) -> Result<T>,
+) -> Result<T> {
+    let info = round.to_account_info();
+    let mut account_data = info.try_borrow_mut_data()?;
+    f(&mut RoundData::from_account_data(&mut account_data[..])?)
+}
+
+/// Append-only journal of a round: the direction each snake chose, how the move
+/// ended and every food placement, one entry per tick after a genesis entry
+/// holding the initial food. Created with the round, delegated and committed
+/// with it, so the whole game can be replayed after settlement. The header is
+/// followed by the entry region (see `MoveLogData` and `LogEntry`).
+#[account(zero_copy)]
+pub struct MoveLog {
+    pub round_id: u64,
+    /// The round's `Round::scatter_seed`, set with the genesis entry.
+    pub scatter_seed: u64,
+    /// Bytes of the entry region in use.
+    pub len: u32,
+    /// Ticks recorded after the genesis entry; the round's move_count unless
+    /// the log filled up.
+    pub move_count: u32,
+    /// Offset of the newest entry, so clients following the log can decode it
+    /// without walking the region.
+    pub last_entry: u32,
+    /// Copied from the round; replay tools pick their engine by it.
+    pub rules_version: u16,
+    /// Spawn heads and `Direction` discriminants, set with the genesis entry.
+    pub alpha_head: u16,
+    pub beta_head: u16,
+    pub alpha_dir: u8,
+    pub beta_dir: u8,
+    /// Copied from the round so the log replays on its own.
+    pub map_id: u8,
+    pub board_size: u8,
+    pub mode: u8,
+    pub food_count: u8,
+    pub toroidal: u8,
+    /// Set once an entry did not fit; later ticks are not recorded. Only logs
+    /// created before they were sized for the longest entry every tick can
+    /// fill up.
+    pub full: u8,
+    pub bump: u8,
+    /// Zero; spare bytes that keep the header 8-byte aligned.
+    pub reserved: [u8; 5],
+}
+
+/// A move log account split into its header and entry region.
+struct MoveLogData<'a> {
+    log: &'a mut MoveLog,
+    entries: &'a mut [u8],
+}
+
+impl<'a> MoveLogData<'a> {
+    fn from_account_data(data: &'a mut [u8]) -> Result<Self> {
+        let header_len = std::mem::size_of::<MoveLog>();
+        require!(data.len() >= 8 + header_len, ErrorCode::AccountDidNotDeserialize);
+        let (header, entries) = data[8..].split_at_mut(header_len);
+        Ok(Self {
+            log: bytemuck::from_bytes_mut(header),
+            entries,
+        })
+    }
+
+    // A full log stops growing instead of failing the move, so a long game is
+    // never stuck on its journal. Logs sized by `move_log_space` never fill.
+    fn append(&mut self, entry: &LogEntry) -> bool {
+        let start = self.log.len as usize;
+        let bytes = entry.as_bytes();
+        if self.log.full != 0 || start + bytes.len() > self.entries.len() {
+            self.log.full = 1;
+            return false;
+        }
+        self.entries[start..start + bytes.len()].copy_from_slice(bytes);
+        self.log.last_entry = start as u32;
+        self.log.len = (start + bytes.len()) as u32;
+        true
+    }
+
+    // Entry 0: spawns in the header and the initial food, written before the
+    // first tick.
+    fn record_genesis(&mut self, data: &RoundData) {
+        let round = &*data.round;
+        let header = &mut *self.log;
+        header.alpha_head = round.alpha_head;
+        header.beta_head = round.beta_head;
+        header.alpha_dir = round.alpha_dir;
+        header.beta_dir = round.beta_dir;
+        header.map_id = round.map_id;
+        header.scatter_seed = round.scatter_seed();
+        let mut entry = LogEntry::new(0);
+        for (list, slots) in food_snapshot(data).iter().enumerate() {
+            for (slot, (cell, kind)) in slots[..round.food_count as usize].iter().enumerate() {
+                entry.push_food(list, slot, *kind, *cell);
+            }
+        }
+        self.append(&entry);
+    }
+}
+
+// Runs `f` on a move log's header and entries, borrowing the account data for
+// the duration of the call.
+fn with_move_log<T>(
+    log: &AccountLoader<MoveLog>,
+    f: impl FnOnce(&mut MoveLogData) -> Result<T>,
+) -> Result<T> {
+    let info = log.to_account_info();
+    let mut account_data = info.try_borrow_mut_data()?;
+    f(&mut MoveLogData::from_account_data(&mut account_data[..])?)
+}
+
+/// One move log entry:
+/// - byte 0: a nibble per snake, alpha low and beta high, holding the chosen
+///   `Direction` in bits 0-1 and a `MOVE_*` outcome in bits 2-3;
+/// - byte 1: number of food placements that follow;
+/// - 3 bytes per placement: slot in bits 0-1, list (0 alpha, 1 beta) in bit 2
+///   and the food `CELL_*` kind in bits 4-7, then the cell as a little-endian
+///   u16.
+///
+/// Placements are listed in the order they happened, so a slot may show up
+/// twice in one tick. Each removes the slot's previous item, if still on the
+/// board, before its own lands; on a shared board a slot's two items are placed
+/// as a pair (Alpha's list, then Beta's) and both previous items go first.
+/// Replays apply the moves, then the placements, then that move's shrinking
+/// wall: an item the wall covers is always replaced later in the same entry.
+struct LogEntry {
+    bytes: [u8; MAX_LOG_ENTRY_LEN],
+    len: usize,
+}
+
+impl LogEntry {
+    fn new(snakes: u8) -> Self {
+        let mut bytes = [0; MAX_LOG_ENTRY_LEN];
+        bytes[0] = snakes;
+        Self { bytes, len: 2 }
+    }
+
+    fn push_food(&mut self, list: usize, slot: usize, kind: u8, cell: u16) {
+        let [low, high] = cell.to_le_bytes();
+        let tag = slot as u8 | (list as u8) << 2 | kind << 4;
+        self.bytes[self.len..self.len + 3].copy_from_slice(&[tag, low, high]);
+        self.bytes[1] += 1;
+        self.len += 3;
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Food placed during one tick, in order, for the move log. A tick replaces a
+/// slot of either list at most twice: once for eaten or expired food, and once
+/// more if the shrinking wall then covers the new item.
+#[derive(Clone, Copy)]
+struct FoodJournal {
+    placements: [FoodPlacement; MAX_TICK_PLACEMENTS],
+    len: usize,
+}
+
+#[derive(Clone, Copy, Default)]
+struct FoodPlacement {
+    list: usize,
+    slot: usize,
+    kind: u8,
+    cell: u16,
+}
+
+impl FoodJournal {
+    fn new() -> Self {
+        Self { placements: [FoodPlacement::default(); MAX_TICK_PLACEMENTS], len: 0 }
+    }
+
+    fn push(&mut self, list: usize, slot: usize, kind: u8, cell: u16) {
+        self.placements[self.len] = FoodPlacement { list, slot, kind, cell };
+        self.len += 1;
+    }
+
+    fn placements(&self) -> &[FoodPlacement] {
+        &self.placements[..self.len]
+    }
+}
+
+/// Admin-managed round presets; create_round copies one onto the new round.
+#[account]
+pub struct RoundTemplate {
+    pub template_id: u32,
+    pub setup: RoundSetup,
+    pub duration: i64,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    /// Retired templates stay readable but cannot start new rounds.
+    pub retired: bool,
     pub bump: u8,
 }
 
+impl RoundTemplate {
+    fn apply(&mut self, params: &TemplateParams) {
+        self.setup = params.setup;
+        self.duration = params.duration;
+        self.min_bet = params.min_bet;
+        self.max_bet = params.max_bet;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TemplateParams {
+    pub setup: RoundSetup,
+    pub duration: i64,
+    /// Per-bet limits in lamports, within MIN_BET_LAMPORTS..=MAX_BET_LAMPORTS.
+    pub min_bet: u64,
+    pub max_bet: u64,
+}
+
+impl TemplateParams {
+    fn validate(&self) -> Result<()> {
+        require!(self.duration > 0, MagicBetError::InvalidDuration);
+        require!(
+            MIN_BET_LAMPORTS <= self.min_bet
+                && self.min_bet <= self.max_bet
+                && self.max_bet <= MAX_BET_LAMPORTS,
+            MagicBetError::InvalidBetLimits
+        );
+        self.setup.validate()
+    }
+}
+
 #[account]
 pub struct Bet {
     pub round_id: u64,
@@ -690,6 +1789,160 @@ pub enum RoundStatus {
     Active,
     InProgress,
     Settled,
+    Voided,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SeedSource {
+    /// Randomness requested from `oracle` at close_betting, delivered by callback.
+    /// `oracle` must be `VRF_ORACLE_PROGRAM_ID`.
+    Vrf { oracle: Pubkey },
+    /// Operator reveals the preimage of `commitment` (sha256) after
+    /// `Round.seed_slot`; it is mixed with that slot's hash. The operator can
+    /// work out the boards once the slot passes and veto the round by not
+    /// revealing, which voids it at the seed deadline.
+    CommitReveal { commitment: [u8; 32] },
+    /// Bettor entropy plus the hash of `Round.seed_slot` seeds the round at
+    /// seed_from_entropy, with no oracle or operator secret.
+    BettorEntropy,
+}
+
+/// Game setup carried by a `RoundTemplate` and copied onto each round.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct RoundSetup {
+    /// Id into the `STRATEGIES` registry.
+    pub alpha_strategy: u8,
+    pub beta_strategy: u8,
+    pub mode: RoundMode,
+    /// Food items in play per board, 1..=MAX_FOOD.
+    pub food_count: u8,
+    pub food_odds: FoodOdds,
+    /// Index into `MAP_LAYOUTS`, or `MAP_FROM_SEED` (255) to draw one at seeding.
+    pub map_id: u8,
+    pub rules: RoundRules,
+}
+
+impl RoundSetup {
+    fn validate(&self) -> Result<()> {
+        require!(
+            strategy_by_id(self.alpha_strategy).is_some()
+                && strategy_by_id(self.beta_strategy).is_some(),
+            MagicBetError::UnknownStrategy
+        );
+        require!(
+            (1..=MAX_FOOD as u8).contains(&self.food_count),
+            MagicBetError::InvalidFoodCount
+        );
+        require!(
+            self.map_id == MAP_FROM_SEED
+                || (self.map_id as usize) < MAP_LAYOUTS.len()
+                    && (self.map_id == 0 || self.rules.board_size as usize >= BOARD_SIZE),
+            MagicBetError::UnknownMap
+        );
+        self.rules.validate()?;
+        require!(
+            self.rules.max_moves <= max_logged_moves(self.food_count),
+            MagicBetError::InvalidRules
+        );
+        self.food_odds.validate()
+    }
+}
+
+/// Game pacing, fixed per round at create_round.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct RoundRules {
+    /// Hard move cap; the round is resolved on score once it is reached. At
+    /// most 726, 390, 267 or 202 with 1 to 4 food items, so the move log fits
+    /// in one account (`max_logged_moves`).
+    pub max_moves: u32,
+    /// Food respawns mirrored on both boards up to and including this move.
+    pub food_mirror_until: u32,
+    /// First move of the shrinking wall, which then closes in every
+    /// `shrink_interval` moves.
+    pub shrink_start: u32,
+    pub shrink_interval: u32,
+    /// Board width and height, MIN_BOARD_SIZE..=MAX_BOARD_SIZE.
+    pub board_size: u8,
+    /// Snakes leave one edge and come back in on the opposite one, and the
+    /// shrinking wall drops as scattered blocks instead of closing in as a
+    /// ring; see `is_in_shrunk_wall`.
+    pub toroidal: bool,
+    /// `TieBreak` ids in the order the winner is decided by, zero-padded;
+    /// all zero means `DEFAULT_TIE_BREAKS`.
+    pub tie_breaks: [u8; MAX_TIE_BREAKS],
+}
+
+impl Default for RoundRules {
+    fn default() -> Self {
+        Self {
+            max_moves: MAX_MOVES,
+            food_mirror_until: FOOD_MIRROR_UNTIL_MOVE,
+            shrink_start: SHRINK_START_MOVE,
+            shrink_interval: SHRINK_INTERVAL,
+            board_size: BOARD_SIZE as u8,
+            toroidal: false,
+            tie_breaks: [0; MAX_TIE_BREAKS],
+        }
+    }
+}
+
+impl RoundRules {
+    fn validate(&self) -> Result<()> {
+        require!(
+            (1..=MAX_MOVES_CAP).contains(&self.max_moves)
+                && self.shrink_interval > 0
+                && (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&(self.board_size as usize)),
+            MagicBetError::InvalidRules
+        );
+        let chain = &self.tie_breaks;
+        let len = chain.iter().position(|id| *id == 0).unwrap_or(MAX_TIE_BREAKS);
+        // Survival leads every chain so a dead snake never beats a live one.
+        require!(
+            (len == 0 || chain[0] == TieBreak::Survival as u8)
+                && chain[len..].iter().all(|id| *id == 0)
+                && chain[..len]
+                    .iter()
+                    .enumerate()
+                    .all(|(index, id)| TieBreak::from_u8(*id).is_some() && !chain[..index].contains(id)),
+            MagicBetError::InvalidTieBreaks
+        );
+        Ok(())
+    }
+}
+
+/// Chance, in percent, that a spawned food item is special; the rest of 100 is
+/// plain food. Golden is worth GOLDEN_POINTS, bonus is worth BONUS_POINTS but
+/// expires after `bonus_ttl` moves, poison shrinks the snake.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FoodOdds {
+    pub golden_pct: u8,
+    pub poison_pct: u8,
+    pub bonus_pct: u8,
+    pub bonus_ttl: u16,
+}
+
+impl FoodOdds {
+    fn validate(&self) -> Result<()> {
+        let total = self.golden_pct as u16 + self.poison_pct as u16 + self.bonus_pct as u16;
+        require!(
+            total <= 100 && (self.bonus_pct == 0 || self.bonus_ttl > 0),
+            MagicBetError::InvalidFoodOdds
+        );
+        Ok(())
+    }
+
+    fn all_plain(&self) -> bool {
+        self.golden_pct == 0 && self.poison_pct == 0 && self.bonus_pct == 0
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RoundMode {
+    /// Each snake plays alone on its own board.
+    Separate,
+    /// Both snakes share `alpha_board` (mirrored into `beta_board`) and can
+    /// block and kill each other.
+    Shared,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -745,131 +1998,318 @@ pub enum MagicBetError {
     UnclaimedWinningBet,
     #[msg("Invalid board setup")]
     InvalidBoardSetup,
+    #[msg("Randomness oracle is not the program's pinned VRF oracle")]
+    InvalidOracle,
+    #[msg("Round seed has already been fulfilled")]
+    RoundAlreadySeeded,
+    #[msg("Round seed has not been fulfilled yet")]
+    RoundNotSeeded,
+    #[msg("Instruction does not match the round seed source")]
+    InvalidSeedSource,
+    #[msg("Revealed secret does not match the commitment")]
+    InvalidSeedReveal,
+    #[msg("Seed deadline has passed")]
+    SeedDeadlinePassed,
+    #[msg("Seed deadline has not passed yet")]
+    SeedDeadlineNotReached,
+    #[msg("Round is not voided")]
+    RoundNotVoided,
+    #[msg("Voided round still has unrefunded bets")]
+    RefundsPending,
+    #[msg("Unknown snake strategy id")]
+    UnknownStrategy,
+    #[msg("Food count must be between 1 and MAX_FOOD")]
+    InvalidFoodCount,
+    #[msg("Food odds exceed 100% or bonus food has no lifetime")]
+    InvalidFoodOdds,
+    #[msg("Unknown map layout id, or board too small for it")]
+    UnknownMap,
+    #[msg("Invalid round rules")]
+    InvalidRules,
+    #[msg("Round template is retired")]
+    TemplateRetired,
+    #[msg("Bet limits must satisfy MIN_BET_LAMPORTS <= min <= max <= MAX_BET_LAMPORTS")]
+    InvalidBetLimits,
+    #[msg("Move count must be between 1 and MAX_MOVES_PER_BATCH")]
+    InvalidMoveCount,
+    #[msg("Unknown rules version")]
+    UnknownRulesVersion,
+    #[msg("Tie-breaks must be distinct known criteria led by survival, zero-padded at the end")]
+    InvalidTieBreaks,
+    #[msg("Seed slot hash is not in SlotHashes yet, or has aged out")]
+    SeedSlotHashUnavailable,
+    #[msg("Account is not a legacy round")]
+    InvalidLegacyRound,
+    #[msg("This build cannot request randomness from the pinned VRF oracle")]
+    VrfUnsupported,
 }
 
-#[derive(Clone)]
-struct SnakeRuntime {
-    board: [u8; BOARD_CELLS],
-    seed: u64,
+/// Engine generations. create_round stamps the current one on the round, and
+/// every tick and replay of that round runs it, so changing the engine never
+/// changes how an existing round plays. A change to strategies, food RNG,
+/// spawns, shrink or winner rules adds a variant and branches on it; existing
+/// variants keep their behaviour and are never removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum RulesVersion {
+    V1 = 1,
+    /// Spawn cell and heading are picked from the round seed; initial food
+    /// keeps its distance from both heads.
+    V2 = 2,
+    /// SplitMix64 streams per snake and purpose replace the shared LCG; see
+    /// `Rng`.
+    V3 = 3,
+    /// Food only lands on cells the snake it is for can reach; see
+    /// `pick_food_cell`.
+    V4 = 4,
+    /// Shortest-path searches give up after `PATH_SEARCH_LIMIT` cells; see
+    /// `first_step_towards`.
+    V5 = 5,
+    /// A snake that dies on the move its tail left gets the tail back, so its
+    /// length is the same however it died; see `SnakeRuntime::restore_tail`.
+    V6 = 6,
+    /// The toroidal shrinking wall's blocks are ranked from a stream split
+    /// off the round seed instead of the round id; see `scatter_rank`.
+    V7 = 7,
+}
+
+impl RulesVersion {
+    const CURRENT: Self = Self::V7;
+
+    fn from_u16(value: u16) -> Option<Self> {
+        match value {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            3 => Some(Self::V3),
+            4 => Some(Self::V4),
+            5 => Some(Self::V5),
+            6 => Some(Self::V6),
+            7 => Some(Self::V7),
+            _ => None,
+        }
+    }
+}
+
+/// Criteria that decide a finished round, stored per round as ids in
+/// `RoundRules.tie_breaks`. Ids are part of the protocol: append, never
+/// renumber.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TieBreak {
+    /// A live snake beats a dead one.
+    Survival = 1,
+    /// Higher score.
+    Score = 2,
+    /// Longer body.
+    Length = 3,
+    /// Later death move; a live snake outlasts any death.
+    DeathMove = 4,
+    /// Fewer steps from the head to the nearest food in play.
+    FoodDistance = 5,
+    /// Fewer moves since the last scoring meal, counted to the snake's death
+    /// or, while it lives, the current move.
+    MovesWithoutEating = 6,
+}
+
+impl TieBreak {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Survival),
+            2 => Some(Self::Score),
+            3 => Some(Self::Length),
+            4 => Some(Self::DeathMove),
+            5 => Some(Self::FoodDistance),
+            6 => Some(Self::MovesWithoutEating),
+            _ => None,
+        }
+    }
+
+    // Alpha's (`alpha`) or Beta's standing under this criterion; higher is
+    // better.
+    fn standing(self, round: &Round, alpha: bool) -> i64 {
+        let side = |alpha_value: u32, beta_value: u32| if alpha { alpha_value } else { beta_value };
+        let alive = side(round.alpha_alive as u32, round.beta_alive as u32) != 0;
+        let death_move = side(round.alpha_death_move, round.beta_death_move);
+        match self {
+            Self::Survival => alive as i64,
+            Self::Score => side(round.alpha_score, round.beta_score) as i64,
+            Self::Length => side(round.alpha_len as u32, round.beta_len as u32) as i64,
+            Self::DeathMove if alive => i64::MAX,
+            Self::DeathMove => death_move as i64,
+            Self::FoodDistance => {
+                let head = side(round.alpha_head as u32, round.beta_head as u32) as u16;
+                let slots = round.food_count as usize;
+                let (own, other) = if alpha {
+                    (&round.alpha_food, &round.beta_food)
+                } else {
+                    (&round.beta_food, &round.alpha_food)
+                };
+                // On a shared board the other list is in play for both snakes.
+                let other_slots = if round.mode() == RoundMode::Shared { slots } else { 0 };
+                let nearest = own[..slots]
+                    .iter()
+                    .chain(&other[..other_slots])
+                    .map(|food| manhattan(round.board_size as usize, round.toroidal != 0, head, *food))
+                    .min()
+                    .unwrap_or(u16::MAX);
+                -(nearest as i64)
+            }
+            Self::MovesWithoutEating => {
+                let last_move = if alive { round.move_count } else { death_move };
+                let last_meal = side(round.alpha_last_meal, round.beta_last_meal);
+                -(last_move.saturating_sub(last_meal) as i64)
+            }
+        }
+    }
+}
+
+struct SnakeRuntime<'a> {
+    size: usize,
+    /// Toroidal board: moves wrap at the edges.
+    wrap: bool,
+    board: Board<'a>,
+    body: &'a mut [u16],
+    tail: u16,
+    len: u16,
+    body_cell: u8,
+    head_cell: u8,
+    /// This snake's food stream.
+    rng: Rng,
+    /// The paired food stream; only Alpha's runtime draws from it and writes
+    /// it back, see `paired_rng`.
+    pair_rng: Rng,
+    /// Food only lands where this snake can reach it.
+    reachable_food: bool,
+    /// Shortest-path searches stop at `PATH_SEARCH_LIMIT` cells.
+    capped_search: bool,
+    /// Dying snakes get back the tail that left on their last move.
+    keeps_tail: bool,
+    /// Cached `food_reach`; cleared whenever a move or the shrinking wall
+    /// changes the board, so food placed between changes shares one fill.
+    reach: Option<CellSet>,
+    /// Food list this snake's items are logged under: 0 Alpha, 1 Beta.
+    list: usize,
+    /// Food placed this tick. Shared-board pairs all go in Alpha's journal so
+    /// their order survives.
+    placed: FoodJournal,
     score: u32,
     alive: bool,
     head: u16,
-    food: u16,
+    food: [u16; MAX_FOOD],
+    food_count: u8,
+    food_expiry: [u32; MAX_FOOD],
+    food_odds: FoodOdds,
     dir: Direction,
     death_move: Option<u32>,
+    last_meal: u32,
 }
 
-impl SnakeRuntime {
-    fn from_alpha(round: &Round) -> Self {
+impl<'a> SnakeRuntime<'a> {
+    fn from_alpha(round: &Round, board: Board<'a>, body: &'a mut [u16]) -> Self {
         Self {
-            board: round.alpha_board,
-            seed: round.alpha_seed,
+            size: round.board_size as usize,
+            wrap: round.toroidal != 0,
+            board,
+            body,
+            tail: round.alpha_tail,
+            len: round.alpha_len,
+            body_cell: CELL_BODY,
+            head_cell: CELL_HEAD,
+            rng: Rng::new(round.alpha_seed, round.uses_lcg()),
+            pair_rng: Rng::new(round.food_seed, round.uses_lcg()),
+            reachable_food: round.places_reachable_food(),
+            capped_search: round.caps_path_search(),
+            keeps_tail: round.keeps_tail_on_death(),
+            reach: None,
+            list: 0,
+            placed: FoodJournal::new(),
             score: round.alpha_score,
-            alive: round.alpha_alive,
+            alive: round.alpha_alive != 0,
             head: round.alpha_head,
             food: round.alpha_food,
-            dir: round.alpha_dir,
-            death_move: round.alpha_death_move,
+            food_count: round.food_count,
+            food_expiry: round.alpha_food_expiry,
+            food_odds: round.food_odds(),
+            dir: DIRECTIONS[round.alpha_dir as usize],
+            death_move: (round.alpha_death_move != 0).then_some(round.alpha_death_move),
+            last_meal: round.alpha_last_meal,
         }
     }
 
-    fn from_beta(round: &Round) -> Self {
+    fn from_beta(round: &Round, board: Board<'a>, body: &'a mut [u16]) -> Self {
         Self {
-            board: round.beta_board,
-            seed: round.beta_seed,
+            size: round.board_size as usize,
+            wrap: round.toroidal != 0,
+            board,
+            body,
+            tail: round.beta_tail,
+            len: round.beta_len,
+            body_cell: beta_body_cell(round.mode()),
+            head_cell: beta_head_cell(round.mode()),
+            rng: Rng::new(round.beta_seed, round.uses_lcg()),
+            pair_rng: Rng::new(round.food_seed, round.uses_lcg()),
+            reachable_food: round.places_reachable_food(),
+            capped_search: round.caps_path_search(),
+            keeps_tail: round.keeps_tail_on_death(),
+            reach: None,
+            list: 1,
+            placed: FoodJournal::new(),
             score: round.beta_score,
-            alive: round.beta_alive,
+            alive: round.beta_alive != 0,
             head: round.beta_head,
             food: round.beta_food,
-            dir: round.beta_dir,
-            death_move: round.beta_death_move,
+            food_count: round.food_count,
+            food_expiry: round.beta_food_expiry,
+            food_odds: round.food_odds(),
+            dir: DIRECTIONS[round.beta_dir as usize],
+            death_move: (round.beta_death_move != 0).then_some(round.beta_death_move),
+            last_meal: round.beta_last_meal,
         }
     }
 
+    // Boards and bodies are edited in place; only the scalars are copied back.
     fn write_back_alpha(self, round: &mut Round) {
-        round.alpha_board = self.board;
-        round.alpha_seed = self.seed;
+        round.alpha_tail = self.tail;
+        round.alpha_len = self.len;
+        round.alpha_seed = self.rng.state;
+        round.food_seed = self.pair_rng.state;
         round.alpha_score = self.score;
-        round.alpha_alive = self.alive;
+        round.alpha_alive = self.alive as u8;
         round.alpha_head = self.head;
         round.alpha_food = self.food;
-        round.alpha_dir = self.dir;
-        round.alpha_death_move = self.death_move;
+        round.alpha_food_expiry = self.food_expiry;
+        round.alpha_dir = self.dir as u8;
+        round.alpha_death_move = self.death_move.unwrap_or(0);
+        round.alpha_last_meal = self.last_meal;
     }
 
     fn write_back_beta(self, round: &mut Round) {
-        round.beta_board = self.board;
-        round.beta_seed = self.seed;
+        round.beta_tail = self.tail;
+        round.beta_len = self.len;
+        round.beta_seed = self.rng.state;
         round.beta_score = self.score;
-        round.beta_alive = self.alive;
+        round.beta_alive = self.alive as u8;
         round.beta_head = self.head;
         round.beta_food = self.food;
-        round.beta_dir = self.dir;
-        round.beta_death_move = self.death_move;
-    }
-
-    fn choose_aggressive_direction(&self) -> Option<Direction> {
-        if !self.alive {
-            return None;
-        }
-
-        let mut candidates = preferred_food_directions(self.head, self.food);
-        candidates.push(turn_right(self.dir));
-        candidates.push(turn_left(self.dir));
-        candidates.push(self.dir);
-        dedup_directions(&mut candidates);
-
-        candidates
-            .into_iter()
-            .find(|dir| is_safe_move(&self.board, step(self.head, *dir)))
+        round.beta_food_expiry = self.food_expiry;
+        round.beta_dir = self.dir as u8;
+        round.beta_death_move = self.death_move.unwrap_or(0);
+        round.beta_last_meal = self.last_meal;
     }
 
-    fn choose_defensive_direction(&self) -> Option<Direction> {
+    fn choose_direction(&self, strategy: &dyn SnakeStrategy) -> Option<Direction> {
         if !self.alive {
             return None;
         }
-
-        let mut best: Option<(Direction, u8, u16)> = None;
-
-        for direction in [Direction::Up, Direction::Right, Direction::Down, Direction::Left] {
-            let next = step(self.head, direction);
-            if !is_safe_move(&self.board, next) {
-                continue;
-            }
-
-            let next_index = match next {
-                Some(value) => value,
-                None => continue,
-            };
-
-            let openness = open_space_score(&self.board, next_index);
-            let distance = manhattan(next_index, self.food);
-
-            let replace = match best {
-                None => true,
-                Some((_, best_open, best_distance)) => {
-                    openness > best_open || (openness == best_open && distance < best_distance)
-                }
-            };
-
-            if replace {
-                best = Some((direction, openness, distance));
-            }
-        }
-
-        if let Some((direction, _, _)) = best {
-            return Some(direction);
-        }
-
-        let mut fallback = preferred_food_directions(self.head, self.food);
-        dedup_directions(&mut fallback);
-
-        fallback
-            .into_iter()
-            .find(|dir| is_safe_move(&self.board, step(self.head, *dir)))
+        // Strategies treat poison as blocked; eating it still beats dying.
+        strategy.choose_direction(self).or_else(|| {
+            DIRECTIONS.into_iter().find(|direction| {
+                step(self.size, self.wrap, self.head, *direction)
+                    .is_some_and(|next| self.board.get(next) == CELL_POISON)
+            })
+        })
     }
 
     fn apply_move(&mut self, direction: Option<Direction>, move_number: u32) -> bool {
+        self.reach = None;
         if !self.alive {
             return false;
         }
@@ -882,7 +2322,7 @@ impl SnakeRuntime {
             }
         };
 
-        let next = step(self.head, direction);
+        let next = step(self.size, self.wrap, self.head, direction);
         let next_index = match next {
             Some(value) => value,
             None => {
@@ -891,468 +2331,3448 @@ impl SnakeRuntime {
             }
         };
 
-        let ate_food = self.board[next_index as usize] == CELL_FOOD || next_index == self.food;
+        let eaten = food_at(&self.board, next_index);
 
-        // Snake grows on food by skipping one decay cycle.
-        if !ate_food {
-            decay_board(&mut self.board);
+        // Snake grows on food by keeping its tail for one move. The tail leaves
+        // before the collision check, so chasing your own tail is legal.
+        let vacated = if grows_on(eaten) { None } else { self.tail_cell() };
+        if vacated.is_some() {
+            self.pop_tail();
         }
 
-        if is_snake(self.board[next_index as usize]) {
+        if is_snake(self.board.get(next_index)) {
             self.mark_dead(move_number);
+            self.restore_tail(vacated);
             return false;
         }
 
-        self.board[next_index as usize] = SNAKE_MAX;
-        self.head = next_index;
-        self.dir = direction;
+        self.advance_head(next_index, direction, eaten, move_number);
+        eaten.is_some()
+    }
+
+    // Resolves a missing or off-board move to death; `None` for dead snakes.
+    fn next_cell(&mut self, direction: Option<Direction>, move_number: u32) -> Option<u16> {
+        if !self.alive {
+            return None;
+        }
+        let next = direction.and_then(|direction| step(self.size, self.wrap, self.head, direction));
+        if next.is_none() {
+            self.mark_dead(move_number);
+        }
+        next
+    }
 
-        if ate_food {
-            self.score = self.score.saturating_add(1);
-            return true;
+    fn advance_head(&mut self, next: u16, direction: Direction, eaten: Option<u8>, move_number: u32) {
+        if is_snake(self.board.get(self.head)) {
+            self.board.set(self.head, self.body_cell);
         }
+        self.push_head(next);
+        self.head = next;
+        self.dir = direction;
 
-        false
+        match eaten {
+            Some(CELL_POISON) => {
+                self.score = self.score.saturating_sub(1);
+                for _ in 0..POISON_SHRINK {
+                    if self.len > 1 {
+                        self.pop_tail();
+                    }
+                }
+            }
+            Some(kind) => {
+                self.score = self.score.saturating_add(food_points(kind));
+                self.last_meal = move_number;
+            }
+            None => {}
+        }
     }
 
-    fn mark_dead(&mut self, move_number: u32) {
-        self.alive = false;
-        if self.death_move.is_none() {
-            self.death_move = Some(move_number);
+    // Stream for food placed on both boards at once. LCG rounds drew it from
+    // Alpha's own stream, so Beta's never moved while food was mirrored.
+    fn paired_rng(&mut self) -> &mut Rng {
+        if self.rng.lcg {
+            &mut self.rng
+        } else {
+            &mut self.pair_rng
         }
     }
-}
 
-fn require_admin_or_agent(config: &Config, signer: Pubkey) -> Result<()> {
-    if signer == config.admin || config.agent == Some(signer) {
-        return Ok(());
+    fn food_slots(&self) -> &[u16] {
+        &self.food[..self.food_count as usize]
     }
-    err!(MagicBetError::Unauthorized)
-}
-
-fn make_seed(round_id: u64, salt: u64) -> u64 {
-    round_id.wrapping_mul(0x9E37_79B9_7F4A_7C15).wrapping_add(salt)
-}
-
-fn next_seed(seed: u64) -> u64 {
-    seed.wrapping_mul(6364136223846793005).wrapping_add(1)
-}
-
-fn initialize_round_state(round: &mut Round) -> Result<()> {
-    let alpha_head = xy_to_index(3, 10).ok_or(MagicBetError::InvalidBoardSetup)?;
-    let beta_head = mirror_index(alpha_head);
 
-    initialize_snake(&mut round.alpha_board, alpha_head, Direction::Right)?;
-    initialize_snake(&mut round.beta_board, beta_head, Direction::Left)?;
+    // Right after eating, the head sits on the eaten food cell.
+    fn eaten_slot(&self) -> Option<usize> {
+        self.food_slots().iter().position(|food| *food == self.head)
+    }
 
-    round.alpha_head = alpha_head;
-    round.beta_head = beta_head;
-    round.alpha_food = alpha_head;
-    round.beta_food = beta_head;
-    round.alpha_dir = Direction::Right;
-    round.beta_dir = Direction::Left;
-
-    let mut alpha = SnakeRuntime::from_alpha(round);
-    let mut beta = SnakeRuntime::from_beta(round);
-    respawn_symmetric_food(&mut alpha, &mut beta)?;
-    alpha.write_back_alpha(round);
-    beta.write_back_beta(round);
+    fn food_expired(&self, slot: usize, move_number: u32) -> bool {
+        self.food_expiry[slot] != 0 && move_number >= self.food_expiry[slot]
+    }
 
-    Ok(())
-}
+    // Food with the fewest Manhattan steps per point; poison is never a target
+    // and earlier slots win ties.
+    fn best_food(&self, from: u16) -> u16 {
+        self.food_slots()
+            .iter()
+            .copied()
+            .filter(|food| is_edible(self.board.get(*food)))
+            .min_by_key(|food| {
+                // Scaled by 6 so 1, 2 and 3 point items divide evenly.
+                u32::from(manhattan(self.size, self.wrap, from, *food)) * 6
+                    / food_points(self.board.get(*food))
+            })
+            .unwrap_or(from)
+    }
 
-fn initialize_snake(board: &mut [u8; BOARD_CELLS], head: u16, dir: Direction) -> Result<()> {
-    board[head as usize] = SNAKE_MAX;
+    fn food_distance(&self, from: u16) -> u16 {
+        manhattan(self.size, self.wrap, from, self.best_food(from))
+    }
 
-    let mut segment = head;
-    for value in [SNAKE_MAX - 1, SNAKE_MAX - 2] {
-        segment = step(segment, opposite(dir)).ok_or(MagicBetError::InvalidBoardSetup)?;
-        board[segment as usize] = value;
+    fn segment(&self, offset: u16) -> u16 {
+        self.body[(self.tail as usize + offset as usize) % self.body.len()]
     }
 
-    Ok(())
-}
+    fn tail_cell(&self) -> Option<u16> {
+        (self.len > 0).then(|| self.segment(0))
+    }
 
-fn respawn_symmetric_food(alpha: &mut SnakeRuntime, beta: &mut SnakeRuntime) -> Result<()> {
-    clear_food_if_present(&mut alpha.board, alpha.food);
-    clear_food_if_present(&mut beta.board, beta.food);
+    // Segment `k` from the tail is gone after k + 1 moves, assuming no food is
+    // eaten meanwhile.
+    fn moves_until_free(&self, index: u16) -> Option<u16> {
+        (0..self.len)
+            .find(|offset| self.segment(*offset) == index)
+            .map(|offset| offset + 1)
+    }
 
-    for _ in 0..BOARD_CELLS {
-        alpha.seed = next_seed(alpha.seed);
-        let alpha_candidate = (alpha.seed % BOARD_CELLS as u64) as u16;
-        let beta_candidate = mirror_index(alpha_candidate);
-        if alpha.board[alpha_candidate as usize] == CELL_EMPTY
-            && beta.board[beta_candidate as usize] == CELL_EMPTY
-        {
-            alpha.food = alpha_candidate;
-            beta.food = beta_candidate;
-            alpha.board[alpha_candidate as usize] = CELL_FOOD;
-            beta.board[beta_candidate as usize] = CELL_FOOD;
-            return Ok(());
+    fn pop_tail(&mut self) {
+        let Some(tail) = self.tail_cell() else {
+            return;
+        };
+        // Shrink walls may have been drawn over the body; leave them in place.
+        if is_snake(self.board.get(tail)) {
+            self.board.set(tail, CELL_EMPTY);
         }
+        self.tail = (self.tail + 1) % self.body.len() as u16;
+        self.len -= 1;
     }
 
-    Err(MagicBetError::InvalidBoardSetup.into())
-}
+    // Puts back the tail `pop_tail` took on a move the snake then died on, so
+    // a snake that runs into a body ends as long as one that leaves the board.
+    // On a shared board the rival may have moved into the freed cell; the
+    // segment still counts towards the length but stays under the rival.
+    fn restore_tail(&mut self, vacated: Option<u16>) {
+        let Some(cell) = vacated.filter(|_| self.keeps_tail) else {
+            return;
+        };
+        let ring = self.body.len() as u16;
+        self.tail = (self.tail + ring - 1) % ring;
+        self.len += 1;
+        if self.board.get(cell) == CELL_EMPTY {
+            let value = if cell == self.head { self.head_cell } else { self.body_cell };
+            self.board.set(cell, value);
+        }
+    }
 
-fn respawn_food_single(runtime: &mut SnakeRuntime) -> Result<()> {
-    clear_food_if_present(&mut runtime.board, runtime.food);
+    fn push_head(&mut self, index: u16) {
+        let slot = (self.tail as usize + self.len as usize) % self.body.len();
+        self.body[slot] = index;
+        self.len += 1;
+        self.board.set(index, self.head_cell);
+    }
 
-    for _ in 0..BOARD_CELLS {
-        runtime.seed = next_seed(runtime.seed);
-        let candidate = (runtime.seed % BOARD_CELLS as u64) as u16;
-        if runtime.board[candidate as usize] == CELL_EMPTY {
-            runtime.food = candidate;
-            runtime.board[candidate as usize] = CELL_FOOD;
-            return Ok(());
+    fn mark_dead(&mut self, move_number: u32) {
+        self.alive = false;
+        if self.death_move.is_none() {
+            self.death_move = Some(move_number);
         }
     }
+}
 
-    Err(MagicBetError::InvalidBoardSetup.into())
+/// A snake AI. Implementations only see their own snake's runtime and must be
+/// deterministic: same board and seed, same move.
+trait SnakeStrategy: Sync {
+    fn choose_direction(&self, snake: &SnakeRuntime) -> Option<Direction>;
 }
 
-fn ensure_food_present(runtime: &mut SnakeRuntime) -> Result<()> {
-    if runtime.board[runtime.food as usize] == CELL_FOOD {
-        return Ok(());
-    }
-    respawn_food_single(runtime)
+/// Built-in AIs, indexed by the strategy id stored on `Round`. Ids are part of
+/// the protocol: append new strategies, never reorder.
+static STRATEGIES: [&dyn SnakeStrategy; 5] = [
+    &GreedyFood,   // 0
+    &Defensive,    // 1
+    &ShortestPath, // 2
+    &TailFollower, // 3
+    &FloodFill,    // 4
+];
+
+fn strategy_by_id(id: u8) -> Option<&'static dyn SnakeStrategy> {
+    STRATEGIES.get(id as usize).copied()
 }
 
-fn clear_food_if_present(board: &mut [u8; BOARD_CELLS], index: u16) {
-    if board[index as usize] == CELL_FOOD {
-        board[index as usize] = CELL_EMPTY;
+/// Chase food first, then turn right/left/straight, whichever is safe.
+struct GreedyFood;
+
+impl SnakeStrategy for GreedyFood {
+    fn choose_direction(&self, snake: &SnakeRuntime) -> Option<Direction> {
+        let mut candidates =
+            preferred_food_directions(snake.size, snake.wrap, snake.head, snake.best_food(snake.head));
+        candidates.push(turn_right(snake.dir));
+        candidates.push(turn_left(snake.dir));
+        candidates.push(snake.dir);
+        dedup_directions(&mut candidates);
+
+        candidates
+            .into_iter()
+            .find(|dir| is_safe_move(&snake.board, step(snake.size, snake.wrap, snake.head, *dir)))
     }
 }
 
-fn should_use_mirrored_food(move_number: u32) -> bool {
-    move_number <= FOOD_MIRROR_UNTIL_MOVE
-}
+/// Prefer the move with the largest reachable area, then the most open
+/// neighbours, then the shortest food distance.
+struct Defensive;
 
-fn shrink_level(move_number: u32) -> i16 {
-    if move_number < SHRINK_START_MOVE {
-        return 0;
+impl SnakeStrategy for Defensive {
+    fn choose_direction(&self, snake: &SnakeRuntime) -> Option<Direction> {
+        let mut best: Option<(Direction, u16, u8, u16)> = None;
+
+        for direction in DIRECTIONS {
+            let next = step(snake.size, snake.wrap, snake.head, direction);
+            if !is_safe_move(&snake.board, next) {
+                continue;
+            }
+
+            let next_index = match next {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let area = reachable_area(snake, next_index);
+            let openness = open_space_score(snake, next_index);
+            let distance = snake.food_distance(next_index);
+
+            let replace = match best {
+                None => true,
+                Some((_, best_area, best_open, best_distance)) => {
+                    (area, openness, std::cmp::Reverse(distance))
+                        > (best_area, best_open, std::cmp::Reverse(best_distance))
+                }
+            };
+
+            if replace {
+                best = Some((direction, area, openness, distance));
+            }
+        }
+
+        if let Some((direction, _, _, _)) = best {
+            return Some(direction);
+        }
+
+        let mut fallback =
+            preferred_food_directions(snake.size, snake.wrap, snake.head, snake.best_food(snake.head));
+        dedup_directions(&mut fallback);
+
+        fallback
+            .into_iter()
+            .find(|dir| is_safe_move(&snake.board, step(snake.size, snake.wrap, snake.head, *dir)))
     }
-    let levels = 1 + ((move_number - SHRINK_START_MOVE) / SHRINK_INTERVAL) as i16;
-    let max_level = (BOARD_SIZE as i16 / 2) - 1;
-    levels.min(max_level)
 }
 
-fn is_in_shrunk_wall(index: u16, level: i16) -> bool {
-    if level <= 0 {
-        return false;
+/// First step of a BFS shortest path to food; defensive when food is cut off.
+struct ShortestPath;
+
+impl SnakeStrategy for ShortestPath {
+    fn choose_direction(&self, snake: &SnakeRuntime) -> Option<Direction> {
+        first_step_towards(snake, snake.head, |index| is_edible(snake.board.get(index)))
+            .or_else(|| Defensive.choose_direction(snake))
     }
-    let (x, y) = index_to_xy(index);
-    let max = BOARD_SIZE as i16 - level;
-    x < level || y < level || x >= max || y >= max
 }
 
-fn apply_shrink_to_runtime(runtime: &mut SnakeRuntime, move_number: u32) -> Result<()> {
-    let level = shrink_level(move_number);
-    if level <= 0 {
-        return Ok(());
-    }
+/// Heads for food only while the step keeps a path back to its own tail, the
+/// one cell guaranteed to free up; otherwise keeps following the tail.
+struct TailFollower;
+
+impl SnakeStrategy for TailFollower {
+    fn choose_direction(&self, snake: &SnakeRuntime) -> Option<Direction> {
+        let tail = match snake.tail_cell() {
+            Some(value) => value,
+            None => return Defensive.choose_direction(snake),
+        };
+        let near_tail = |index: u16| manhattan(snake.size, snake.wrap, index, tail) == 1;
 
-    for index in 0..BOARD_CELLS as u16 {
-        if is_in_shrunk_wall(index, level) {
-            runtime.board[index as usize] = CELL_WALL;
+        if let Some(direction) =
+            first_step_towards(snake, snake.head, |index| is_edible(snake.board.get(index)))
+        {
+            if let Some(next) = step(snake.size, snake.wrap, snake.head, direction) {
+                if near_tail(next) || first_step_towards(snake, next, near_tail).is_some() {
+                    return Some(direction);
+                }
+            }
         }
-    }
 
-    if runtime.alive && is_in_shrunk_wall(runtime.head, level) {
-        runtime.mark_dead(move_number);
+        first_step_towards(snake, snake.head, near_tail)
+            .or_else(|| Defensive.choose_direction(snake))
     }
+}
 
-    if runtime.board[runtime.food as usize] == CELL_WALL {
-        respawn_food_single(runtime)?;
-    }
+/// Moves into the largest reachable region, tie-break on food distance.
+struct FloodFill;
 
-    Ok(())
-}
+impl SnakeStrategy for FloodFill {
+    fn choose_direction(&self, snake: &SnakeRuntime) -> Option<Direction> {
+        let mut best: Option<(Direction, u16, u16)> = None;
 
-fn determine_winner(round: &Round, force: bool) -> Option<AIChoice> {
-    if round.alpha_alive && !round.beta_alive {
-        return Some(AIChoice::Alpha);
-    }
-    if round.beta_alive && !round.alpha_alive {
-        return Some(AIChoice::Beta);
-    }
+        for direction in DIRECTIONS {
+            let next = match step(snake.size, snake.wrap, snake.head, direction) {
+                Some(value) if is_safe_move(&snake.board, Some(value)) => value,
+                _ => continue,
+            };
 
-    if round.alpha_alive && round.beta_alive {
-        if !force {
-            return None;
-        }
+            let area = reachable_area(snake, next);
+            let distance = snake.food_distance(next);
 
-        if round.alpha_score > round.beta_score {
-            return Some(AIChoice::Alpha);
-        }
-        if round.beta_score > round.alpha_score {
-            return Some(AIChoice::Beta);
+            let replace = match best {
+                None => true,
+                Some((_, best_area, best_distance)) => {
+                    area > best_area || (area == best_area && distance < best_distance)
+                }
+            };
+
+            if replace {
+                best = Some((direction, area, distance));
+            }
         }
-        return Some(AIChoice::Draw);
-    }
 
-    if round.alpha_score > round.beta_score {
-        return Some(AIChoice::Alpha);
-    }
-    if round.beta_score > round.alpha_score {
-        return Some(AIChoice::Beta);
+        best.map(|(direction, _, _)| direction)
     }
+}
 
-    match (round.alpha_death_move, round.beta_death_move) {
-        (Some(alpha), Some(beta)) if alpha > beta => Some(AIChoice::Alpha),
-        (Some(alpha), Some(beta)) if beta > alpha => Some(AIChoice::Beta),
-        _ => Some(AIChoice::Draw),
+fn require_admin_or_agent(config: &Config, signer: Pubkey) -> Result<()> {
+    if signer == config.admin || config.agent == Some(signer) {
+        return Ok(());
     }
+    err!(MagicBetError::Unauthorized)
 }
 
-fn max_round_moves(duration: i64) -> u32 {
-    let duration_moves = duration.saturating_mul(10);
-    let bounded = duration_moves.clamp(1, MAX_MOVES as i64);
-    bounded as u32
+#[derive(AnchorSerialize)]
+struct VrfCallbackAccountMeta {
+    pubkey: Pubkey,
+    is_writable: bool,
 }
 
-fn preferred_food_directions(head: u16, food: u16) -> Vec<Direction> {
-    let (hx, hy) = index_to_xy(head);
-    let (fx, fy) = index_to_xy(food);
-
-    let dx = fx - hx;
-    let dy = fy - hy;
+#[derive(AnchorSerialize)]
+struct VrfRequestArgs {
+    caller_seed: [u8; 32],
+    callback_program_id: Pubkey,
+    callback_discriminator: Vec<u8>,
+    callback_accounts: Vec<VrfCallbackAccountMeta>,
+}
 
-    let mut directions = Vec::with_capacity(4);
+fn request_round_randomness(ctx: &Context<CloseBetting>, oracle: Pubkey) -> Result<()> {
+    let (Some(program_identity), Some(oracle_program), Some(oracle_request)) = (
+        ctx.accounts.program_identity.as_ref(),
+        ctx.accounts.oracle_program.as_ref(),
+        ctx.accounts.oracle_request.as_ref(),
+    ) else {
+        return err!(MagicBetError::InvalidOracle);
+    };
+    require_keys_eq!(oracle_program.key(), oracle, MagicBetError::InvalidOracle);
+    let identity_bump = ctx.bumps.program_identity.ok_or(MagicBetError::InvalidOracle)?;
+
+    let round_key = ctx.accounts.round.key();
+    let args = VrfRequestArgs {
+        caller_seed: round_key.to_bytes(),
+        callback_program_id: crate::ID,
+        callback_discriminator: instruction::FulfillRoundSeed::DISCRIMINATOR.to_vec(),
+        callback_accounts: vec![VrfCallbackAccountMeta {
+            pubkey: round_key,
+            is_writable: true,
+        }],
+    };
 
-    if dx.abs() >= dy.abs() {
-        if dx > 0 {
-            directions.push(Direction::Right);
-        } else if dx < 0 {
-            directions.push(Direction::Left);
-        }
+    let mut data = VRF_REQUEST_DISCRIMINATOR.to_vec();
+    args.serialize(&mut data)?;
+
+    let ix = Instruction {
+        program_id: oracle,
+        accounts: vec![
+            AccountMeta::new(ctx.accounts.signer.key(), true),
+            AccountMeta::new_readonly(program_identity.key(), true),
+            AccountMeta::new(oracle_request.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+        ],
+        data,
+    };
 
-        if dy > 0 {
-            directions.push(Direction::Down);
-        } else if dy < 0 {
-            directions.push(Direction::Up);
-        }
-    } else {
-        if dy > 0 {
-            directions.push(Direction::Down);
-        } else if dy < 0 {
-            directions.push(Direction::Up);
-        }
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.signer.to_account_info(),
+            program_identity.clone(),
+            oracle_request.clone(),
+            ctx.accounts.system_program.to_account_info(),
+            oracle_program.clone(),
+        ],
+        &[&[IDENTITY_SEED, &[identity_bump]]],
+    )?;
 
-        if dx > 0 {
-            directions.push(Direction::Right);
-        } else if dx < 0 {
-            directions.push(Direction::Left);
-        }
-    }
+    Ok(())
+}
 
-    directions
+/// The part of a legacy (`round_v2`, Borsh) round that survives migration; the
+/// fields after `duration` only mattered to a game in play.
+#[derive(AnchorDeserialize)]
+struct LegacyRound {
+    round_id: u64,
+    status: RoundStatus,
+    winner: Option<AIChoice>,
+    _boards: [u8; 2 * LEGACY_BOARD_CELLS],
+    _alpha_seed: u64,
+    _beta_seed: u64,
+    alpha_score: u32,
+    beta_score: u32,
+    alpha_alive: bool,
+    beta_alive: bool,
+    move_count: u32,
+    alpha_pool: u64,
+    beta_pool: u64,
+    start_time: i64,
+    end_time: Option<i64>,
+    duration: i64,
 }
 
-fn dedup_directions(directions: &mut Vec<Direction>) {
-    let mut deduped = Vec::with_capacity(directions.len());
-    for direction in directions.iter().copied() {
-        if !deduped.contains(&direction) {
-            deduped.push(direction);
-        }
+impl LegacyRound {
+    // Legacy rounds carry the same account discriminator as `Round`.
+    fn parse(data: &[u8]) -> Result<Self> {
+        require!(
+            data.get(..8) == Some(Round::DISCRIMINATOR),
+            MagicBetError::InvalidLegacyRound
+        );
+        Self::deserialize(&mut &data[8..]).map_err(|_| error!(MagicBetError::InvalidLegacyRound))
     }
-    *directions = deduped;
 }
 
-fn decay_board(board: &mut [u8; BOARD_CELLS]) {
-    for cell in board.iter_mut() {
-        if is_snake(*cell) {
-            *cell = cell.saturating_sub(1);
-            if *cell < SNAKE_MIN {
-                *cell = CELL_EMPTY;
-            }
+fn migrate_round(round: &mut Round, legacy: &LegacyRound, now: i64) {
+    round.round_id = legacy.round_id;
+    round.alpha_score = legacy.alpha_score;
+    round.beta_score = legacy.beta_score;
+    round.alpha_alive = legacy.alpha_alive as u8;
+    round.beta_alive = legacy.beta_alive as u8;
+    round.move_count = legacy.move_count;
+    round.alpha_pool = legacy.alpha_pool;
+    round.beta_pool = legacy.beta_pool;
+    round.start_time = legacy.start_time;
+    round.duration = legacy.duration;
+    round.board_size = BOARD_SIZE as u8;
+    round.rules_version = RulesVersion::V1 as u16;
+    match (legacy.status, legacy.winner) {
+        (RoundStatus::Settled, Some(winner)) => {
+            round.status = RoundStatus::Settled as u8;
+            round.set_winner(winner);
+            round.end_time = legacy.end_time.unwrap_or(now);
+        }
+        _ => {
+            round.status = RoundStatus::Voided as u8;
+            round.end_time = now;
         }
     }
 }
 
-fn open_space_score(board: &[u8; BOARD_CELLS], from: u16) -> u8 {
-    let mut score = 0u8;
-    for direction in [Direction::Up, Direction::Right, Direction::Down, Direction::Left] {
-        if is_safe_move(board, step(from, direction)) {
-            score = score.saturating_add(1);
+// Hash of the first block at or after `slot` in SlotHashes sysvar data; `None`
+// until that block exists, or once it may have aged out. Layout: u64 entry
+// count, then (u64 slot, [u8; 32] hash) entries, newest first.
+fn slot_hash_at(data: &[u8], slot: u64) -> Option<[u8; 32]> {
+    let count = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+    let mut found = None;
+    for entry in data.get(8..)?.chunks_exact(40).take(count) {
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().ok()?);
+        if entry_slot < slot {
+            return found;
+        }
+        found = entry[8..].try_into().ok();
+        if entry_slot == slot {
+            return found;
         }
     }
-    score
+    // Every entry is newer than `slot`, so its block may have been dropped.
+    None
 }
 
-fn is_safe_move(board: &[u8; BOARD_CELLS], next: Option<u16>) -> bool {
-    let next = match next {
-        Some(value) => value,
-        None => return false,
-    };
+// Every seed source is mixed with the bettors' entropy, so neither the operator
+// nor the oracle alone knows the boards before betting closes.
+fn seed_round(data: &mut RoundData, randomness: &[u8; 32]) -> Result<()> {
+    let round = &mut *data.round;
+    let mixed = hashv(&[randomness.as_ref(), round.entropy_accumulator.as_ref()]).to_bytes();
+    let (alpha_seed, beta_seed) = derive_round_seeds(&mixed);
+    round.alpha_seed = alpha_seed;
+    round.beta_seed = beta_seed;
+    initialize_round_state(data)?;
+    data.round.seeded = 1;
+    Ok(())
+}
 
-    let cell = board[next as usize];
-    !is_snake(cell) && cell != CELL_WALL
+fn derive_round_seeds(randomness: &[u8; 32]) -> (u64, u64) {
+    let alpha = hashv(&[randomness.as_ref(), b"alpha"]).to_bytes();
+    let beta = hashv(&[randomness.as_ref(), b"beta"]).to_bytes();
+    (seed_from_bytes(&alpha), seed_from_bytes(&beta))
 }
 
-fn is_snake(value: u8) -> bool {
-    value >= SNAKE_MIN
+fn seed_from_bytes(bytes: &[u8; 32]) -> u64 {
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(seed)
 }
 
-fn manhattan(a: u16, b: u16) -> u16 {
-    let (ax, ay) = index_to_xy(a);
-    let (bx, by) = index_to_xy(b);
-    ((ax - bx).abs() + (ay - by).abs()) as u16
+fn next_seed(seed: u64) -> u64 {
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1)
 }
 
-fn mirror_index(index: u16) -> u16 {
-    let (x, y) = index_to_xy(index);
-    let mirrored_x = (BOARD_SIZE as i16 - 1) - x;
-    xy_to_index(mirrored_x, y).unwrap_or(index)
+// Seed of the stream `label`, split off the round seeds by hashing, so streams
+// for different purposes are independent of each other.
+fn stream_seed(round: &Round, label: &[u8]) -> u64 {
+    seed_from_bytes(
+        &hashv(&[round.alpha_seed.to_le_bytes().as_ref(), round.beta_seed.to_le_bytes().as_ref(), label])
+            .to_bytes(),
+    )
 }
 
-fn index_to_xy(index: u16) -> (i16, i16) {
-    let x = (index as usize % BOARD_SIZE) as i16;
-    let y = (index as usize / BOARD_SIZE) as i16;
-    (x, y)
+/// A random stream whose whole state is one u64 on the round. Rounds before
+/// `RulesVersion::V3` step the `next_seed` LCG and take the state modulo the
+/// range, whose low bits cycle quickly (the lowest one alternates). From V3
+/// each stream is SplitMix64 and draws take the high bits of the product
+/// with the range (Lemire), biased by under 2^-52 for board-sized ranges.
+#[derive(Clone, Copy)]
+struct Rng {
+    state: u64,
+    lcg: bool,
 }
 
-fn xy_to_index(x: i16, y: i16) -> Option<u16> {
-    if x < 0 || y < 0 || x >= BOARD_SIZE as i16 || y >= BOARD_SIZE as i16 {
-        return None;
+impl Rng {
+    fn new(state: u64, lcg: bool) -> Self {
+        Self { state, lcg }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if self.lcg {
+            self.state = next_seed(self.state);
+            return self.state;
+        }
+        self.state = self.state.wrapping_add(SPLITMIX_GAMMA);
+        mix64(self.state)
+    }
+
+    // Uniform in 0..range.
+    fn below(&mut self, range: u64) -> u64 {
+        let value = self.next_u64();
+        if self.lcg {
+            return value % range;
+        }
+        ((u128::from(value) * u128::from(range)) >> 64) as u64
+    }
+
+    fn percent(&mut self) -> u8 {
+        if self.lcg {
+            // High bits; the low bits of the LCG cycle quickly.
+            return ((self.next_u64() >> 32) % 100) as u8;
+        }
+        self.below(100) as u8
     }
-    Some((y as usize * BOARD_SIZE + x as usize) as u16)
 }
 
-fn step(index: u16, direction: Direction) -> Option<u16> {
-    let (x, y) = index_to_xy(index);
-    let (dx, dy) = match direction {
-        Direction::Up => (0, -1),
-        Direction::Right => (1, 0),
-        Direction::Down => (0, 1),
-        Direction::Left => (-1, 0),
-    };
-    xy_to_index(x + dx, y + dy)
+const SPLITMIX_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// SplitMix64 output function.
+fn mix64(value: u64) -> u64 {
+    let value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
 }
 
-fn opposite(direction: Direction) -> Direction {
-    match direction {
-        Direction::Up => Direction::Down,
-        Direction::Right => Direction::Left,
-        Direction::Down => Direction::Up,
-        Direction::Left => Direction::Right,
+fn initialize_round_state(data: &mut RoundData) -> Result<()> {
+    let round = &mut *data.round;
+    // Rounds from an engine generation this build does not know never start.
+    let version = round.rules_version()?;
+    let size = round.board_size as usize;
+    let mode = round.mode();
+    data.alpha_board.fill(CELL_EMPTY);
+    data.beta_board.fill(CELL_EMPTY);
+    data.alpha_body.fill(0);
+    data.beta_body.fill(0);
+
+    // Maps are centred on boards larger than BOARD_SIZE and spawns move with
+    // them; smaller boards are always open.
+    let offset = (size.saturating_sub(BOARD_SIZE) / 2) as i16;
+    let spawn_y = offset + (size as i16 / 2).min(10);
+    let fixed_head = xy_to_index(size, offset + 3, spawn_y).ok_or(MagicBetError::InvalidBoardSetup)?;
+
+    if round.map_id == MAP_FROM_SEED {
+        let layouts = MAP_LAYOUTS.len() as u64;
+        round.map_id = if size < BOARD_SIZE {
+            0
+        } else if round.uses_lcg() {
+            ((round.alpha_seed ^ round.beta_seed.rotate_left(32)) % layouts) as u8
+        } else {
+            Rng::new(stream_seed(round, b"map"), false).below(layouts) as u8
+        };
     }
-}
+    let layout = MAP_LAYOUTS
+        .get(round.map_id as usize)
+        .ok_or(MagicBetError::UnknownMap)?;
+    draw_map(&mut data.alpha_board, size, offset, layout);
+    draw_map(&mut data.beta_board, size, offset, layout);
+
+    // Beta always starts as Alpha's mirror image, on the mirrored map.
+    let (alpha_head, alpha_dir) = if version >= RulesVersion::V2 {
+        let seed = stream_seed(round, b"spawn");
+        seeded_spawn(&data.alpha_board, size, round.toroidal != 0, seed, round.uses_lcg())
+            .unwrap_or((fixed_head, Direction::Right))
+    } else {
+        (fixed_head, Direction::Right)
+    };
+    let beta_head = mirror_index(size, alpha_head);
+    let beta_dir = mirror_direction(alpha_dir);
+
+    initialize_snake(
+        &mut data.alpha_board,
+        data.alpha_body,
+        size,
+        alpha_head,
+        alpha_dir,
+        CELL_BODY,
+        CELL_HEAD,
+    )?;
+    let beta_board = match mode {
+        RoundMode::Separate => &mut data.beta_board,
+        RoundMode::Shared => &mut data.alpha_board,
+    };
+    initialize_snake(
+        beta_board,
+        data.beta_body,
+        size,
+        beta_head,
+        beta_dir,
+        beta_body_cell(mode),
+        beta_head_cell(mode),
+    )?;
+    if mode == RoundMode::Shared {
+        data.beta_board.copy_from(&data.alpha_board);
+    }
+    round.alpha_tail = 0;
+    round.alpha_len = INITIAL_SNAKE_LEN;
+    round.beta_tail = 0;
+    round.beta_len = INITIAL_SNAKE_LEN;
 
-#[cfg(test)]
-mod simulation_tests {
-    use super::*;
+    round.alpha_head = alpha_head;
+    round.beta_head = beta_head;
+    round.alpha_food = [alpha_head; MAX_FOOD];
+    round.beta_food = [beta_head; MAX_FOOD];
+    round.alpha_dir = alpha_dir as u8;
+    round.beta_dir = beta_dir as u8;
+    if !round.uses_lcg() {
+        // The round seeds become the snakes' food streams from here on.
+        round.food_seed = stream_seed(round, b"paired food");
+        round.shrink_seed = stream_seed(round, b"shrink");
+        let alpha_seed = stream_seed(round, b"alpha food");
+        round.beta_seed = stream_seed(round, b"beta food");
+        round.alpha_seed = alpha_seed;
+    }
 
-    fn blank_round(round_id: u64, duration: i64) -> Round {
-        Round {
-            round_id,
-            status: RoundStatus::InProgress,
-            winner: None,
-            alpha_board: [CELL_EMPTY; BOARD_CELLS],
-            beta_board: [CELL_EMPTY; BOARD_CELLS],
-            alpha_seed: make_seed(round_id, 0xA11A_A11A_A11A_A11A),
-            beta_seed: make_seed(round_id, 0xB37A_B37A_B37A_B37A),
-            alpha_score: 0,
-            beta_score: 0,
-            alpha_alive: true,
-            beta_alive: true,
-            move_count: 0,
-            alpha_pool: 0,
-            beta_pool: 0,
-            start_time: 0,
-            end_time: None,
-            duration,
-            alpha_head: 0,
-            beta_head: 0,
-            alpha_food: 0,
-            beta_food: 0,
-            alpha_dir: Direction::Right,
-            beta_dir: Direction::Left,
-            alpha_death_move: None,
-            beta_death_move: None,
-            bump: 0,
-        }
-    }
-
-    fn simulate_round(round_id: u64, duration: i64) -> AIChoice {
-        let mut round = blank_round(round_id, duration);
-        initialize_round_state(&mut round).expect("round init should succeed");
+    let mut alpha = SnakeRuntime::from_alpha(round, data.alpha_board.reborrow(), data.alpha_body);
+    let mut beta = SnakeRuntime::from_beta(round, data.beta_board.reborrow(), data.beta_body);
+    let tries = if version >= RulesVersion::V2 { SPAWN_FOOD_TRIES } else { 1 };
+    for slot in 0..round.food_count as usize {
+        for _ in 0..tries {
+            match mode {
+                RoundMode::Separate => respawn_symmetric_food(&mut alpha, &mut beta, slot, 0)?,
+                RoundMode::Shared => respawn_shared_food(&mut alpha, &mut beta, slot, 0)?,
+            }
+            if !food_crowds_spawn(&alpha, &beta, slot, mode) {
+                break;
+            }
+        }
+    }
+    alpha.write_back_alpha(round);
+    beta.write_back_beta(round);
+    round.state_hash = [0; 32];
+    chain_state_hash(data);
 
-        for _ in 0..max_round_moves(duration) {
-            let move_number = round.move_count.saturating_add(1);
-            let mut alpha = SnakeRuntime::from_alpha(&round);
-            let mut beta = SnakeRuntime::from_beta(&round);
+    Ok(())
+}
+
+// What a tick did that the round does not keep, for the move log: the
+// directions the snakes chose and the food placed, in order.
+struct Tick {
+    directions: [Option<Direction>; 2],
+    placements: [FoodJournal; 2],
+}
 
-            let alpha_direction = alpha.choose_aggressive_direction();
-            let beta_direction = beta.choose_aggressive_direction();
+// One engine tick: both snakes pick a move against the same snapshot, move,
+// eat and get squeezed by the shrinking wall, then the winner is checked.
+fn advance_round(data: &mut RoundData) -> Result<Tick> {
+    let round = &mut *data.round;
+    let move_number = round
+        .move_count
+        .checked_add(1)
+        .ok_or(MagicBetError::ArithmeticOverflow)?;
+    let rules = round.rules();
+    round.rules_version()?;
+
+    let mut alpha = SnakeRuntime::from_alpha(round, data.alpha_board.reborrow(), data.alpha_body);
+    let mut beta = SnakeRuntime::from_beta(round, data.beta_board.reborrow(), data.beta_body);
+
+    let alpha_strategy =
+        strategy_by_id(round.alpha_strategy).ok_or(MagicBetError::UnknownStrategy)?;
+    let beta_strategy =
+        strategy_by_id(round.beta_strategy).ok_or(MagicBetError::UnknownStrategy)?;
+    let alpha_direction = alpha.choose_direction(alpha_strategy);
+    let beta_direction = beta.choose_direction(beta_strategy);
+
+    match round.mode() {
+        RoundMode::Separate => {
             let alpha_ate = alpha.apply_move(alpha_direction, move_number);
             let beta_ate = beta.apply_move(beta_direction, move_number);
-            if should_use_mirrored_food(move_number) {
-                if alpha_ate || beta_ate {
-                    respawn_symmetric_food(&mut alpha, &mut beta)
-                        .expect("symmetric food respawn should succeed");
+
+            let alpha_slot = alpha_ate.then(|| alpha.eaten_slot()).flatten();
+            let beta_slot = beta_ate.then(|| beta.eaten_slot()).flatten();
+
+            let mirrored = should_use_mirrored_food(&rules, move_number);
+            if mirrored {
+                // A slot eaten on either board is replaced on both.
+                if let Some(slot) = alpha_slot {
+                    respawn_symmetric_food(&mut alpha, &mut beta, slot, move_number)?;
+                }
+                if let Some(slot) = beta_slot.filter(|slot| Some(*slot) != alpha_slot) {
+                    respawn_symmetric_food(&mut alpha, &mut beta, slot, move_number)?;
                 }
             } else {
-                if alpha_ate {
-                    respawn_food_single(&mut alpha).expect("alpha food respawn should succeed");
+                if let Some(slot) = alpha_slot {
+                    respawn_food_single(&mut alpha, slot, move_number)?;
                 }
-                if beta_ate {
-                    respawn_food_single(&mut beta).expect("beta food respawn should succeed");
+                if let Some(slot) = beta_slot {
+                    respawn_food_single(&mut beta, slot, move_number)?;
                 }
             }
 
-            apply_shrink_to_runtime(&mut alpha, move_number).expect("alpha shrink should succeed");
-            apply_shrink_to_runtime(&mut beta, move_number).expect("beta shrink should succeed");
-            ensure_food_present(&mut alpha).expect("alpha food should exist");
-            ensure_food_present(&mut beta).expect("beta food should exist");
-
-            alpha.write_back_alpha(&mut round);
-            beta.write_back_beta(&mut round);
-            round.move_count = move_number;
+            // Expired bonus food is replaced the same way as eaten food.
+            for slot in 0..round.food_count as usize {
+                let alpha_expired = alpha.food_expired(slot, move_number);
+                let beta_expired = beta.food_expired(slot, move_number);
+                if mirrored {
+                    if alpha_expired || beta_expired {
+                        respawn_symmetric_food(&mut alpha, &mut beta, slot, move_number)?;
+                    }
+                    continue;
+                }
+                if alpha_expired {
+                    respawn_food_single(&mut alpha, slot, move_number)?;
+                }
+                if beta_expired {
+                    respawn_food_single(&mut beta, slot, move_number)?;
+                }
+            }
 
-            if let Some(winner) = determine_winner(&round, false) {
-                return winner;
+            apply_shrink_to_runtime(&mut alpha, &rules, round.scatter_seed(), move_number)?;
+            apply_shrink_to_runtime(&mut beta, &rules, round.scatter_seed(), move_number)?;
+            ensure_food_present(&mut alpha, move_number)?;
+            ensure_food_present(&mut beta, move_number)?;
+        }
+        RoundMode::Shared => {
+            let (alpha_ate, beta_ate) =
+                apply_shared_moves(&mut alpha, &mut beta, alpha_direction, beta_direction, move_number);
+            let alpha_slot = alpha_ate.then(|| shared_food_slot(&alpha, &beta, alpha.head)).flatten();
+            let beta_slot = beta_ate.then(|| shared_food_slot(&alpha, &beta, beta.head)).flatten();
+            if let Some(slot) = alpha_slot {
+                respawn_shared_food(&mut alpha, &mut beta, slot, move_number)?;
+            }
+            if let Some(slot) = beta_slot.filter(|slot| Some(*slot) != alpha_slot) {
+                respawn_shared_food(&mut alpha, &mut beta, slot, move_number)?;
+            }
+            for slot in 0..round.food_count as usize {
+                if alpha.food_expired(slot, move_number) {
+                    respawn_shared_food(&mut alpha, &mut beta, slot, move_number)?;
+                }
             }
+            apply_shrink_shared(&mut alpha, &mut beta, &rules, round.scatter_seed(), move_number)?;
         }
+    }
+
+    let placements = [alpha.placed, beta.placed];
+    alpha.write_back_alpha(round);
+    beta.write_back_beta(round);
+    round.move_count = move_number;
+    chain_state_hash(data);
+    let round = &mut *data.round;
 
-        determine_winner(&round, true).unwrap_or(AIChoice::Draw)
+    let force_resolution = round.move_count >= max_round_moves(&rules, round.duration);
+    if let Some(winner) = determine_winner(round, force_resolution) {
+        round.set_winner(winner);
     }
 
-    #[test]
-    fn fairness_snapshot_500_rounds() {
-        let mut alpha = 0u32;
-        let mut beta = 0u32;
-        let mut draw = 0u32;
+    Ok(Tick {
+        directions: [alpha_direction, beta_direction],
+        placements,
+    })
+}
 
-        for round_id in 1..=500 {
-            match simulate_round(round_id, 45) {
-                AIChoice::Alpha => alpha += 1,
-                AIChoice::Beta => beta += 1,
-                AIChoice::Draw => draw += 1,
-            }
-        }
+// Commits to the frame just produced: sha256(previous hash, move_count as u32
+// LE, both packed boards, alpha_score and beta_score as u32 LE). The layout is
+// seeded from a zero hash at move 0, so anyone holding the frames can rebuild
+// the chain and find the first move where their copy diverges.
+fn chain_state_hash(data: &mut RoundData) {
+    let round = &*data.round;
+    let hash = hashv(&[
+        round.state_hash.as_ref(),
+        &round.move_count.to_le_bytes(),
+        data.alpha_board.bytes,
+        data.beta_board.bytes,
+        &round.alpha_score.to_le_bytes(),
+        &round.beta_score.to_le_bytes(),
+    ]);
+    data.round.state_hash = hash.to_bytes();
+}
 
-        println!(
-            "fairness snapshot => alpha: {alpha}, beta: {beta}, draw: {draw}"
+// One tick with its move log entry; an empty log gets the genesis entry first.
+fn advance_round_logged(data: &mut RoundData, log: &mut MoveLogData) -> Result<()> {
+    if data.round.move_count == 0 && log.log.len == 0 {
+        log.record_genesis(data);
+    }
+    let round = &*data.round;
+    let heads = [round.alpha_head, round.beta_head];
+    let alive = [round.alpha_alive != 0, round.beta_alive != 0];
+
+    let tick = advance_round(data)?;
+    let directions = tick.directions;
+
+    let round = &*data.round;
+    let heads_after = [round.alpha_head, round.beta_head];
+    let alive_after = [round.alpha_alive != 0, round.beta_alive != 0];
+    let mut snakes = 0;
+    for i in 0..2 {
+        let outcome = move_outcome(
+            alive[i] && directions[i].is_some(),
+            heads[i] != heads_after[i],
+            alive_after[i],
         );
-        assert_eq!(alpha + beta + draw, 500);
+        let direction = directions[i].map_or(0, |direction| direction as u8);
+        snakes |= (direction | outcome << 2) << (4 * i);
     }
+    let mut entry = LogEntry::new(snakes);
+    for placement in tick.placements.iter().flat_map(FoodJournal::placements) {
+        entry.push_food(placement.list, placement.slot, placement.kind, placement.cell);
+    }
+    if log.append(&entry) {
+        log.log.move_count += 1;
+    }
+    Ok(())
 }
 
-fn turn_right(direction: Direction) -> Direction {
-    match direction {
-        Direction::Up => Direction::Right,
-        Direction::Right => Direction::Down,
-        Direction::Down => Direction::Left,
-        Direction::Left => Direction::Up,
+fn move_outcome(tried: bool, moved: bool, alive: bool) -> u8 {
+    match (tried, moved, alive) {
+        (false, _, _) => MOVE_IDLE,
+        (true, true, true) => MOVE_MOVED,
+        (true, true, false) => MOVE_MOVED_THEN_DIED,
+        (true, false, _) => MOVE_CRASHED,
     }
 }
 
-fn turn_left(direction: Direction) -> Direction {
-    match direction {
-        Direction::Up => Direction::Left,
-        Direction::Right => Direction::Up,
-        Direction::Down => Direction::Right,
-        Direction::Left => Direction::Down,
+// Food slots of both lists as (cell, kind), for the genesis entry.
+fn food_snapshot(data: &RoundData) -> [[(u16, u8); MAX_FOOD]; 2] {
+    let round = &*data.round;
+    let slots = round.food_count as usize;
+    let mut food = [[(0, CELL_EMPTY); MAX_FOOD]; 2];
+    for (entry, cell) in food[0].iter_mut().zip(&round.alpha_food).take(slots) {
+        *entry = (*cell, data.alpha_board.get(*cell));
+    }
+    for (entry, cell) in food[1].iter_mut().zip(&round.beta_food).take(slots) {
+        *entry = (*cell, data.beta_board.get(*cell));
+    }
+    food
+}
+
+// Runs up to `count` logged ticks, stopping as soon as a winner is set; returns
+// how many ran.
+// The first tick always runs, like execute_move; later ones only while
+// `remaining_units` reports at least TICK_COMPUTE_RESERVE.
+fn advance_round_by(
+    data: &mut RoundData,
+    log: &mut MoveLogData,
+    count: u8,
+    remaining_units: impl Fn() -> u64,
+) -> Result<u8> {
+    let mut ticks = 0;
+    while ticks < count && data.round.winner().is_none() {
+        if ticks > 0 && remaining_units() < TICK_COMPUTE_RESERVE {
+            break;
+        }
+        advance_round_logged(data, log)?;
+        ticks += 1;
+    }
+    Ok(ticks)
+}
+
+// Simultaneous moves on a shared board. Both runtimes hold a copy of the same
+// board and are kept in sync here. Resolution order:
+// 1. a snake with no legal direction dies in place;
+// 2. tails of snakes that are not eating leave the board;
+// 3. both heads entering the same cell is a head-on: the longer snake survives,
+//    equal lengths both die;
+// 4. any other head entering a body (own or rival, including a swap through
+//    each other's necks) dies;
+// 5. survivors move in and eat.
+fn apply_shared_moves<'a>(
+    alpha: &mut SnakeRuntime<'a>,
+    beta: &mut SnakeRuntime<'a>,
+    alpha_direction: Option<Direction>,
+    beta_direction: Option<Direction>,
+    move_number: u32,
+) -> (bool, bool) {
+    (alpha.reach, beta.reach) = (None, None);
+    let alpha_next = alpha.next_cell(alpha_direction, move_number);
+    let beta_next = beta.next_cell(beta_direction, move_number);
+    let alpha_eats = alpha_next.and_then(|next| food_at(&alpha.board, next));
+    let beta_eats = beta_next.and_then(|next| food_at(&beta.board, next));
+
+    let alpha_vacated = alpha_next.filter(|_| !grows_on(alpha_eats)).and(alpha.tail_cell());
+    if alpha_vacated.is_some() {
+        alpha.pop_tail();
+    }
+    beta.board.copy_from(&alpha.board);
+    let beta_vacated = beta_next.filter(|_| !grows_on(beta_eats)).and(beta.tail_cell());
+    if beta_vacated.is_some() {
+        beta.pop_tail();
+    }
+    alpha.board.copy_from(&beta.board);
+
+    if let (Some(alpha_cell), Some(beta_cell)) = (alpha_next, beta_next) {
+        if alpha_cell == beta_cell {
+            if alpha.len <= beta.len {
+                alpha.mark_dead(move_number);
+            }
+            if beta.len <= alpha.len {
+                beta.mark_dead(move_number);
+            }
+        }
+    }
+    for (runtime, next) in [(&mut *alpha, alpha_next), (&mut *beta, beta_next)] {
+        if let Some(next) = next {
+            if runtime.alive && is_snake(runtime.board.get(next)) {
+                runtime.mark_dead(move_number);
+            }
+        }
+    }
+
+    let alpha_ate = match (alpha_next, alpha_direction) {
+        (Some(next), Some(direction)) if alpha.alive => {
+            alpha.advance_head(next, direction, alpha_eats, move_number);
+            alpha_eats.is_some()
+        }
+        _ => false,
+    };
+    beta.board.copy_from(&alpha.board);
+    let beta_ate = match (beta_next, beta_direction) {
+        (Some(next), Some(direction)) if beta.alive => {
+            beta.advance_head(next, direction, beta_eats, move_number);
+            beta_eats.is_some()
+        }
+        _ => false,
+    };
+    alpha.board.copy_from(&beta.board);
+
+    // Snakes that died this move get their tails back once the survivor has
+    // moved, so a tail it followed into stays its own.
+    if !alpha.alive && alpha_next.is_some() {
+        alpha.restore_tail(alpha_vacated);
+        beta.board.copy_from(&alpha.board);
+    }
+    if !beta.alive && beta_next.is_some() {
+        beta.restore_tail(beta_vacated);
+        alpha.board.copy_from(&beta.board);
+    }
+
+    (alpha_ate, beta_ate)
+}
+
+fn beta_body_cell(mode: RoundMode) -> u8 {
+    match mode {
+        RoundMode::Separate => CELL_BODY,
+        RoundMode::Shared => CELL_BETA_BODY,
+    }
+}
+
+fn beta_head_cell(mode: RoundMode) -> u8 {
+    match mode {
+        RoundMode::Separate => CELL_HEAD,
+        RoundMode::Shared => CELL_BETA_HEAD,
+    }
+}
+
+fn draw_map(board: &mut Board, size: usize, offset: i16, layout: &[(i16, i16, i16, i16)]) {
+    for &(left, top, width, height) in layout {
+        for y in offset + top..offset + top + height {
+            for x in offset + left..offset + left + width {
+                if let Some(index) = xy_to_index(size, x, y) {
+                    board.set(index, CELL_WALL);
+                    board.set(mirror_index(size, index), CELL_WALL);
+                }
+            }
+        }
+    }
+}
+
+// Seeded spawn for Alpha among the (cell, heading) candidates that fit. A
+// candidate fits when the head, the body trailing behind it and
+// SPAWN_CLEAR_AHEAD cells in front are all on open cells left of the centre
+// gap, so the mirrored Beta fits too and the two never touch on a shared
+// board. LCG rounds walk from a seed-picked candidate to the next one that
+// fits (in index order, wrapping around), which favours candidates right after
+// long runs of misfits; from V3 every fitting candidate is equally likely.
+fn seeded_spawn(board: &Board, size: usize, wrap: bool, seed: u64, lcg: bool) -> Option<(u16, Direction)> {
+    let total = board.len() * DIRECTIONS.len();
+    let fitting = || {
+        (0..total).filter_map(|candidate| {
+            let head = (candidate / DIRECTIONS.len()) as u16;
+            let dir = DIRECTIONS[candidate % DIRECTIONS.len()];
+            spawn_fits(board, size, wrap, head, dir).then_some((candidate, head, dir))
+        })
+    };
+    let spawn = if lcg {
+        let start = (seed % total as u64) as usize;
+        fitting().find(|(candidate, _, _)| *candidate >= start).or_else(|| fitting().next())
+    } else {
+        let count = fitting().count() as u64;
+        if count == 0 {
+            return None;
+        }
+        fitting().nth(Rng::new(seed, false).below(count) as usize)
+    };
+    spawn.map(|(_, head, dir)| (head, dir))
+}
+
+fn spawn_fits(board: &Board, size: usize, wrap: bool, head: u16, dir: Direction) -> bool {
+    // On a toroidal board the left edge touches Beta's side too.
+    let (first, limit) = (wrap as i16, (size / 2) as i16 - 1);
+    let open = |cell: u16| {
+        let x = index_to_xy(size, cell).0;
+        board.get(cell) == CELL_EMPTY && (first..limit).contains(&x)
+    };
+    let run = |direction: Direction, len: u16| {
+        let mut cell = head;
+        (0..len).all(|_| match step(size, false, cell, direction) {
+            Some(next) if open(next) => {
+                cell = next;
+                true
+            }
+            _ => false,
+        })
+    };
+    open(head) && run(opposite(dir), INITIAL_SNAKE_LEN - 1) && run(dir, SPAWN_CLEAR_AHEAD)
+}
+
+// Initial food in `slot` that some snake could reach within
+// SPAWN_FOOD_CLEARANCE steps. On a shared board either snake may eat either
+// item of the pair.
+fn food_crowds_spawn(alpha: &SnakeRuntime, beta: &SnakeRuntime, slot: usize, mode: RoundMode) -> bool {
+    let near = |head: u16, food: u16| manhattan(alpha.size, alpha.wrap, head, food) <= SPAWN_FOOD_CLEARANCE;
+    let (alpha_head, beta_head) = (alpha.head, beta.head);
+    let (alpha_food, beta_food) = (alpha.food[slot], beta.food[slot]);
+    near(alpha_head, alpha_food)
+        || near(beta_head, beta_food)
+        || (mode == RoundMode::Shared && (near(alpha_head, beta_food) || near(beta_head, alpha_food)))
+}
+
+// Lays out INITIAL_SNAKE_LEN segments trailing behind `head`, stored tail first.
+// Every segment must land on an empty cell, so a map cannot wall in a spawn.
+fn initialize_snake(
+    board: &mut Board,
+    body: &mut [u16],
+    size: usize,
+    head: u16,
+    dir: Direction,
+    body_cell: u8,
+    head_cell: u8,
+) -> Result<()> {
+    require!(board.get(head) == CELL_EMPTY, MagicBetError::InvalidBoardSetup);
+    board.set(head, head_cell);
+    body[INITIAL_SNAKE_LEN as usize - 1] = head;
+
+    let mut segment = head;
+    for offset in (0..INITIAL_SNAKE_LEN as usize - 1).rev() {
+        segment = step(size, false, segment, opposite(dir)).ok_or(MagicBetError::InvalidBoardSetup)?;
+        require!(board.get(segment) == CELL_EMPTY, MagicBetError::InvalidBoardSetup);
+        board.set(segment, body_cell);
+        body[offset] = segment;
+    }
+
+    Ok(())
+}
+
+fn respawn_symmetric_food(
+    alpha: &mut SnakeRuntime,
+    beta: &mut SnakeRuntime,
+    slot: usize,
+    move_number: u32,
+) -> Result<()> {
+    clear_food_if_present(&mut alpha.board, alpha.food[slot]);
+    clear_food_if_present(&mut beta.board, beta.food[slot]);
+    let odds = alpha.food_odds;
+    let kind = roll_food_kind(alpha.paired_rng(), &odds);
+    let expiry = food_expiry_for(kind, &odds, move_number);
+
+    let size = alpha.size;
+    let cells = alpha.board.len();
+    let free = |alpha: &SnakeRuntime, beta: &SnakeRuntime, cell: u16| {
+        alpha.board.get(cell) == CELL_EMPTY && beta.board.get(mirror_index(size, cell)) == CELL_EMPTY
+    };
+    let alpha_candidate = if alpha.reachable_food {
+        // Each item must be reachable on its own board.
+        let reach = [food_reach(alpha), food_reach(beta)];
+        let mut rng = *alpha.paired_rng();
+        let candidate = pick_food_cell(&mut rng, cells, |cell, reachable| {
+            free(alpha, beta, cell)
+                && (!reachable
+                    || (reaches(&reach[0], cell) && reaches(&reach[1], mirror_index(size, cell))))
+        });
+        *alpha.paired_rng() = rng;
+        candidate
+    } else {
+        (0..cells).find_map(|_| {
+            let candidate = alpha.paired_rng().below(cells as u64) as u16;
+            free(alpha, beta, candidate).then_some(candidate)
+        })
+    };
+    let alpha_candidate = alpha_candidate.ok_or(MagicBetError::InvalidBoardSetup)?;
+    let beta_candidate = mirror_index(size, alpha_candidate);
+
+    alpha.food[slot] = alpha_candidate;
+    beta.food[slot] = beta_candidate;
+    alpha.food_expiry[slot] = expiry;
+    beta.food_expiry[slot] = expiry;
+    alpha.board.set(alpha_candidate, kind);
+    beta.board.set(beta_candidate, kind);
+    alpha.placed.push(alpha.list, slot, kind, alpha_candidate);
+    beta.placed.push(beta.list, slot, kind, beta_candidate);
+    Ok(())
+}
+
+// Shared boards carry one mirrored food pair per slot; either snake may eat
+// either item, and eating one replaces the pair.
+fn respawn_shared_food(
+    alpha: &mut SnakeRuntime,
+    beta: &mut SnakeRuntime,
+    slot: usize,
+    move_number: u32,
+) -> Result<()> {
+    clear_food_if_present(&mut alpha.board, alpha.food[slot]);
+    clear_food_if_present(&mut alpha.board, beta.food[slot]);
+    let odds = alpha.food_odds;
+    let kind = roll_food_kind(alpha.paired_rng(), &odds);
+    let expiry = food_expiry_for(kind, &odds, move_number);
+
+    let size = alpha.size;
+    let cells = alpha.board.len();
+    let free = |board: &Board, cell: u16| {
+        let mirrored = mirror_index(size, cell);
+        cell != mirrored && board.get(cell) == CELL_EMPTY && board.get(mirrored) == CELL_EMPTY
+    };
+    let alpha_candidate = if alpha.reachable_food {
+        // Each snake must be able to reach the item on its own side.
+        let reach = [food_reach(alpha), food_reach(beta)];
+        let mut rng = *alpha.paired_rng();
+        let candidate = pick_food_cell(&mut rng, cells, |cell, reachable| {
+            free(&alpha.board, cell)
+                && (!reachable
+                    || (reaches(&reach[0], cell) && reaches(&reach[1], mirror_index(size, cell))))
+        });
+        *alpha.paired_rng() = rng;
+        candidate
+    } else {
+        (0..cells).find_map(|_| {
+            let candidate = alpha.paired_rng().below(cells as u64) as u16;
+            free(&alpha.board, candidate).then_some(candidate)
+        })
+    };
+    let alpha_candidate = alpha_candidate.ok_or(MagicBetError::InvalidBoardSetup)?;
+    let beta_candidate = mirror_index(size, alpha_candidate);
+
+    alpha.board.set(alpha_candidate, kind);
+    alpha.board.set(beta_candidate, kind);
+    alpha.food[slot] = alpha_candidate;
+    beta.food[slot] = beta_candidate;
+    alpha.food_expiry[slot] = expiry;
+    beta.food_expiry[slot] = expiry;
+    beta.board.copy_from(&alpha.board);
+    alpha.placed.push(alpha.list, slot, kind, alpha_candidate);
+    alpha.placed.push(beta.list, slot, kind, beta_candidate);
+    Ok(())
+}
+
+fn shared_food_slot(alpha: &SnakeRuntime, beta: &SnakeRuntime, cell: u16) -> Option<usize> {
+    (0..alpha.food_count as usize).find(|slot| alpha.food[*slot] == cell || beta.food[*slot] == cell)
+}
+
+fn respawn_food_single(runtime: &mut SnakeRuntime, slot: usize, move_number: u32) -> Result<()> {
+    clear_food_if_present(&mut runtime.board, runtime.food[slot]);
+    let kind = roll_food_kind(&mut runtime.rng, &runtime.food_odds);
+    runtime.food_expiry[slot] = food_expiry_for(kind, &runtime.food_odds, move_number);
+
+    let cells = runtime.board.len();
+    let candidate = if runtime.reachable_food {
+        let reach = food_reach(runtime);
+        let mut rng = runtime.rng;
+        let candidate = pick_food_cell(&mut rng, cells, |cell, reachable| {
+            runtime.board.get(cell) == CELL_EMPTY && (!reachable || reaches(&reach, cell))
+        });
+        runtime.rng = rng;
+        candidate
+    } else {
+        (0..cells).find_map(|_| {
+            let candidate = runtime.rng.below(cells as u64) as u16;
+            (runtime.board.get(candidate) == CELL_EMPTY).then_some(candidate)
+        })
+    };
+    let candidate = candidate.ok_or(MagicBetError::InvalidBoardSetup)?;
+    runtime.food[slot] = candidate;
+    runtime.board.set(candidate, kind);
+    runtime.placed.push(runtime.list, slot, kind, candidate);
+    Ok(())
+}
+
+// Food cell drawn uniformly from the cells `eligible(cell, true)` accepts,
+// which are the free cells the snakes can reach. When there are none (the
+// snake is boxed in by walls or bodies), the same draw is made over
+// `eligible(cell, false)`, any free cell, so a round never stalls on food.
+// Each pass checks every cell once and counts the matches in a bitset.
+fn pick_food_cell(rng: &mut Rng, cells: usize, eligible: impl Fn(u16, bool) -> bool) -> Option<u16> {
+    for reachable in [true, false] {
+        let mut matching = CellSet([0; MAX_BOARD_CELLS / 64]);
+        let mut count = 0u64;
+        for cell in 0..cells as u16 {
+            if eligible(cell, reachable) {
+                matching.insert(cell);
+                count += 1;
+            }
+        }
+        if count > 0 {
+            return matching.nth(rng.below(count));
+        }
+    }
+    None
+}
+
+/// Bitset over board cells.
+#[derive(Clone, Copy)]
+struct CellSet([u64; MAX_BOARD_CELLS / 64]);
+
+impl CellSet {
+    fn contains(&self, cell: u16) -> bool {
+        self.0[cell as usize / 64] & (1 << (cell % 64)) != 0
+    }
+
+    fn insert(&mut self, cell: u16) {
+        self.0[cell as usize / 64] |= 1 << (cell % 64);
+    }
+
+    // The `n`th cell in the set, counting from 0 in cell order.
+    fn nth(&self, mut n: u64) -> Option<u16> {
+        for (word_index, word) in self.0.iter().enumerate() {
+            let ones = u64::from(word.count_ones());
+            if n >= ones {
+                n -= ones;
+                continue;
+            }
+            let mut word = *word;
+            for _ in 0..n {
+                word &= word - 1;
+            }
+            return Some((word_index * 64) as u16 + word.trailing_zeros() as u16);
+        }
+        None
+    }
+}
+
+// Cells `runtime`'s snake can walk to from its head as its board stands,
+// through anything but walls and bodies; `None` (no constraint) once the snake
+// is dead. Unlike `reachable_area` this is a full, uncapped fill, so it is run
+// at most once per board change: the result is cached on the runtime until a
+// move or the shrinking wall clears it. Placing food does not change it.
+fn food_reach(runtime: &mut SnakeRuntime) -> Option<CellSet> {
+    if !runtime.alive {
+        return None;
+    }
+    if runtime.reach.is_none() {
+        runtime.reach = Some(fill_reach(runtime));
+    }
+    runtime.reach
+}
+
+fn fill_reach(runtime: &SnakeRuntime) -> CellSet {
+    let board = &runtime.board;
+    let mut seen = CellSet([0; MAX_BOARD_CELLS / 64]);
+    let mut queue = [0u16; MAX_BOARD_CELLS];
+    let (mut read, mut write) = (0usize, 1usize);
+    seen.insert(runtime.head);
+    queue[0] = runtime.head;
+    while read < write {
+        let cell = queue[read];
+        read += 1;
+        for direction in DIRECTIONS {
+            let Some(next) = step(runtime.size, runtime.wrap, cell, direction) else {
+                continue;
+            };
+            let value = board.get(next);
+            if seen.contains(next) || value == CELL_WALL || is_snake(value) {
+                continue;
+            }
+            seen.insert(next);
+            queue[write] = next;
+            write += 1;
+        }
+    }
+    seen
+}
+
+fn reaches(reach: &Option<CellSet>, cell: u16) -> bool {
+    match reach {
+        Some(reach) => reach.contains(cell),
+        None => true,
+    }
+}
+
+fn ensure_food_present(runtime: &mut SnakeRuntime, move_number: u32) -> Result<()> {
+    for slot in 0..runtime.food_count as usize {
+        if !is_food(runtime.board.get(runtime.food[slot])) {
+            respawn_food_single(runtime, slot, move_number)?;
+        }
+    }
+    Ok(())
+}
+
+fn clear_food_if_present(board: &mut Board, index: u16) {
+    if is_food(board.get(index)) {
+        board.set(index, CELL_EMPTY);
+    }
+}
+
+// Rounds without special odds skip the draw, so their food sequence is the
+// same as before food kinds existed.
+fn roll_food_kind(rng: &mut Rng, odds: &FoodOdds) -> u8 {
+    if odds.all_plain() {
+        return CELL_FOOD;
+    }
+    let roll = rng.percent();
+    if roll < odds.golden_pct {
+        CELL_GOLDEN
+    } else if roll < odds.golden_pct + odds.poison_pct {
+        CELL_POISON
+    } else if roll < odds.golden_pct + odds.poison_pct + odds.bonus_pct {
+        CELL_BONUS
+    } else {
+        CELL_FOOD
+    }
+}
+
+fn food_expiry_for(kind: u8, odds: &FoodOdds, move_number: u32) -> u32 {
+    if kind == CELL_BONUS {
+        move_number.saturating_add(odds.bonus_ttl as u32)
+    } else {
+        0
+    }
+}
+
+fn is_food(value: u8) -> bool {
+    matches!(value, CELL_FOOD | CELL_GOLDEN | CELL_POISON | CELL_BONUS)
+}
+
+// Food worth eating; poison is avoided by every strategy.
+fn is_edible(value: u8) -> bool {
+    is_food(value) && value != CELL_POISON
+}
+
+fn food_at(board: &Board, index: u16) -> Option<u8> {
+    let value = board.get(index);
+    is_food(value).then_some(value)
+}
+
+// Poison is eaten but does not grow the snake.
+fn grows_on(eaten: Option<u8>) -> bool {
+    eaten.is_some_and(is_edible)
+}
+
+fn food_points(kind: u8) -> u32 {
+    match kind {
+        CELL_GOLDEN => GOLDEN_POINTS,
+        CELL_BONUS => BONUS_POINTS,
+        _ => 1,
+    }
+}
+
+fn should_use_mirrored_food(rules: &RoundRules, move_number: u32) -> bool {
+    move_number <= rules.food_mirror_until
+}
+
+fn shrink_level(rules: &RoundRules, move_number: u32) -> i16 {
+    if move_number < rules.shrink_start {
+        return 0;
+    }
+    let levels = 1 + (move_number - rules.shrink_start) / rules.shrink_interval;
+    let max_level = (rules.board_size as i16 / 2) - 1;
+    levels.min(max_level as u32) as i16
+}
+
+// The shrinking wall at `level` is a ring `level` cells deep. Toroidal boards
+// have no edge to close in from, so there it covers about as many cells as
+// scattered blocks instead: every mirrored pair of cells gets a rank from
+// `scatter_rank`, and a level covers the pairs ranked below the ring's cell
+// count, so each level keeps the blocks of the one before.
+fn is_in_shrunk_wall(rules: &RoundRules, seed: u64, index: u16, level: i16) -> bool {
+    if level <= 0 {
+        return false;
+    }
+    let size = rules.board_size as usize;
+    if rules.toroidal {
+        let inner = (size as i16 - 2 * level) as u64;
+        let covered = (size * size) as u64 - inner * inner;
+        return scatter_rank(size, seed, index) < covered;
+    }
+    let (x, y) = index_to_xy(size, index);
+    let max = size as i16 - level;
+    x < level || y < level || x >= max || y >= max
+}
+
+// Rank of a cell's mirrored pair, 0..size², hashed from the round's
+// `scatter_seed` (splitmix64), so the block pattern differs between rounds and,
+// from V7, stays unknown until the round is seeded.
+fn scatter_rank(size: usize, seed: u64, index: u16) -> u64 {
+    let pair = u64::from(index.min(mirror_index(size, index)));
+    mix64(seed ^ pair.wrapping_mul(SPLITMIX_GAMMA)) % (size * size) as u64
+}
+
+// Walls up the cells the shrinking wall covers. The ring crushes whatever it
+// reaches, but scattered blocks do not land on snakes: a covered cell under a
+// body walls up once the snake has moved off it.
+fn raise_shrunk_wall(board: &mut Board, rules: &RoundRules, seed: u64, level: i16) {
+    for index in 0..board.len() as u16 {
+        if is_in_shrunk_wall(rules, seed, index, level)
+            && !(rules.toroidal && is_snake(board.get(index)))
+        {
+            board.set(index, CELL_WALL);
+        }
+    }
+}
+
+fn apply_shrink_to_runtime(
+    runtime: &mut SnakeRuntime,
+    rules: &RoundRules,
+    seed: u64,
+    move_number: u32,
+) -> Result<()> {
+    let level = shrink_level(rules, move_number);
+    if level <= 0 {
+        return Ok(());
+    }
+
+    raise_shrunk_wall(&mut runtime.board, rules, seed, level);
+    runtime.reach = None;
+
+    if runtime.alive && runtime.board.get(runtime.head) == CELL_WALL {
+        runtime.mark_dead(move_number);
+    }
+
+    for slot in 0..runtime.food_count as usize {
+        if runtime.board.get(runtime.food[slot]) == CELL_WALL {
+            respawn_food_single(runtime, slot, move_number)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_shrink_shared<'a>(
+    alpha: &mut SnakeRuntime<'a>,
+    beta: &mut SnakeRuntime<'a>,
+    rules: &RoundRules,
+    seed: u64,
+    move_number: u32,
+) -> Result<()> {
+    let level = shrink_level(rules, move_number);
+    if level <= 0 {
+        return Ok(());
+    }
+
+    raise_shrunk_wall(&mut alpha.board, rules, seed, level);
+    beta.board.copy_from(&alpha.board);
+    (alpha.reach, beta.reach) = (None, None);
+
+    for runtime in [&mut *alpha, &mut *beta] {
+        if runtime.alive && runtime.board.get(runtime.head) == CELL_WALL {
+            runtime.mark_dead(move_number);
+        }
+    }
+
+    for slot in 0..alpha.food_count as usize {
+        if !is_food(alpha.board.get(alpha.food[slot]))
+            || !is_food(alpha.board.get(beta.food[slot]))
+        {
+            respawn_shared_food(alpha, beta, slot, move_number)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Winner rules, shared by both modes. A round is over once either snake dies,
+// or with both alive at the move cap (`force`). The round's tie-breaks are then
+// applied in order and the first that separates the snakes decides; if none
+// does, it is a draw. The default chain (survival, score, later death) makes a
+// lone survivor win at once, and otherwise, when both live to the cap or both
+// die (on a shared board that includes a head-on between equal lengths, which
+// kills both on the same move), the higher score wins, then the later death.
+fn determine_winner(round: &Round, force: bool) -> Option<AIChoice> {
+    if round.alpha_alive != 0 && round.beta_alive != 0 && !force {
+        return None;
+    }
+
+    let decided = round.tie_breaks().find_map(|tie_break| {
+        match tie_break.standing(round, true).cmp(&tie_break.standing(round, false)) {
+            std::cmp::Ordering::Greater => Some(AIChoice::Alpha),
+            std::cmp::Ordering::Less => Some(AIChoice::Beta),
+            std::cmp::Ordering::Equal => None,
+        }
+    });
+    Some(decided.unwrap_or(AIChoice::Draw))
+}
+
+fn max_round_moves(rules: &RoundRules, duration: i64) -> u32 {
+    let duration_moves = duration.saturating_mul(10);
+    let bounded = duration_moves.clamp(1, rules.max_moves as i64);
+    bounded as u32
+}
+
+fn preferred_food_directions(size: usize, wrap: bool, head: u16, food: u16) -> Vec<Direction> {
+    let (dx, dy) = offset_between(size, wrap, head, food);
+
+    let mut directions = Vec::with_capacity(4);
+
+    if dx.abs() >= dy.abs() {
+        if dx > 0 {
+            directions.push(Direction::Right);
+        } else if dx < 0 {
+            directions.push(Direction::Left);
+        }
+
+        if dy > 0 {
+            directions.push(Direction::Down);
+        } else if dy < 0 {
+            directions.push(Direction::Up);
+        }
+    } else {
+        if dy > 0 {
+            directions.push(Direction::Down);
+        } else if dy < 0 {
+            directions.push(Direction::Up);
+        }
+
+        if dx > 0 {
+            directions.push(Direction::Right);
+        } else if dx < 0 {
+            directions.push(Direction::Left);
+        }
+    }
+
+    directions
+}
+
+fn dedup_directions(directions: &mut Vec<Direction>) {
+    let mut deduped = Vec::with_capacity(directions.len());
+    for direction in directions.iter().copied() {
+        if !deduped.contains(&direction) {
+            deduped.push(direction);
+        }
+    }
+    *directions = deduped;
+}
+
+fn open_space_score(snake: &SnakeRuntime, from: u16) -> u8 {
+    let mut score = 0u8;
+    for direction in DIRECTIONS {
+        if is_safe_move(&snake.board, step(snake.size, snake.wrap, from, direction)) {
+            score = score.saturating_add(1);
+        }
+    }
+    score
+}
+
+// Breadth-first search over safe cells from `head`; returns the first step of
+// a shortest path to any safe cell accepted by `is_target`. From rules version
+// 5 the search queues at most PATH_SEARCH_LIMIT cells and gives up on targets
+// further out; earlier versions search the whole board.
+fn first_step_towards(
+    snake: &SnakeRuntime,
+    head: u16,
+    is_target: impl Fn(u16) -> bool,
+) -> Option<Direction> {
+    if snake.capped_search {
+        path_search::<PATH_SEARCH_LIMIT>(snake, head, is_target)
+    } else {
+        path_search::<MAX_BOARD_CELLS>(snake, head, is_target)
+    }
+}
+
+// `first_step_towards` with a queue of LIMIT cells. Each queued cell keeps the
+// first step that reached it at the same position in `first_step`.
+fn path_search<const LIMIT: usize>(
+    snake: &SnakeRuntime,
+    head: u16,
+    is_target: impl Fn(u16) -> bool,
+) -> Option<Direction> {
+    let board = &snake.board;
+    let mut seen = CellSet([0; MAX_BOARD_CELLS / 64]);
+    let mut queue = [0u16; LIMIT];
+    let mut first_step = [Direction::Up; LIMIT];
+    let mut read = 0usize;
+    let mut write = 0usize;
+    seen.insert(head);
+
+    for direction in DIRECTIONS {
+        let next = match step(snake.size, snake.wrap, head, direction) {
+            Some(value) if is_safe_move(board, Some(value)) => value,
+            _ => continue,
+        };
+        if is_target(next) {
+            return Some(direction);
+        }
+        seen.insert(next);
+        queue[write] = next;
+        first_step[write] = direction;
+        write += 1;
+    }
+
+    while read < write {
+        let cell = queue[read];
+        let origin = first_step[read];
+        read += 1;
+
+        for direction in DIRECTIONS {
+            let next = match step(snake.size, snake.wrap, cell, direction) {
+                Some(value) if is_safe_move(board, Some(value)) => value,
+                _ => continue,
+            };
+            if seen.contains(next) {
+                continue;
+            }
+            if is_target(next) {
+                return Some(origin);
+            }
+            if write == LIMIT {
+                continue;
+            }
+            seen.insert(next);
+            queue[write] = next;
+            first_step[write] = origin;
+            write += 1;
+        }
+    }
+
+    None
+}
+
+// Counts cells reachable from `from` (the next head position), stopping at
+// FLOOD_FILL_LIMIT. Body cells count as open once the tail will have left them
+// by the time the fill reaches them, so a snake is not scared of its own tail.
+fn reachable_area(snake: &SnakeRuntime, from: u16) -> u16 {
+    let mut visited = [false; MAX_BOARD_CELLS];
+    let mut queue = [(0u16, 0u16); FLOOD_FILL_LIMIT];
+    let mut read = 0usize;
+    let mut write = 1usize;
+    visited[from as usize] = true;
+    queue[0] = (from, 1);
+
+    while read < write {
+        let (cell, moves) = queue[read];
+        read += 1;
+
+        for direction in DIRECTIONS {
+            let next = match step(snake.size, snake.wrap, cell, direction) {
+                Some(value) => value,
+                None => continue,
+            };
+            if visited[next as usize] || !is_open_after(snake, next, moves + 1) {
+                continue;
+            }
+            if write == FLOOD_FILL_LIMIT {
+                return write as u16;
+            }
+            visited[next as usize] = true;
+            queue[write] = (next, moves + 1);
+            write += 1;
+        }
+    }
+
+    write as u16
+}
+
+fn is_open_after(snake: &SnakeRuntime, index: u16, moves: u16) -> bool {
+    let cell = snake.board.get(index);
+    if cell == CELL_WALL {
+        return false;
+    }
+    if !is_snake(cell) {
+        return true;
+    }
+    snake
+        .moves_until_free(index)
+        .is_some_and(|free_after| free_after <= moves)
+}
+
+fn is_safe_move(board: &Board, next: Option<u16>) -> bool {
+    let next = match next {
+        Some(value) => value,
+        None => return false,
+    };
+
+    let cell = board.get(next);
+    !is_snake(cell) && cell != CELL_WALL && cell != CELL_POISON
+}
+
+fn is_snake(value: u8) -> bool {
+    matches!(value, CELL_BODY | CELL_HEAD | CELL_BETA_BODY | CELL_BETA_HEAD)
+}
+
+fn board_cells(board_size: u8) -> usize {
+    board_size as usize * board_size as usize
+}
+
+fn manhattan(size: usize, wrap: bool, a: u16, b: u16) -> u16 {
+    let (dx, dy) = offset_between(size, wrap, a, b);
+    (dx.abs() + dy.abs()) as u16
+}
+
+// (dx, dy) from `from` to `to`. With `wrap` each axis takes the shorter way
+// around the board. Exactly half-way across keeps its sign, so mirrored snakes
+// still see mirrored offsets.
+fn offset_between(size: usize, wrap: bool, from: u16, to: u16) -> (i16, i16) {
+    let (fx, fy) = index_to_xy(size, from);
+    let (tx, ty) = index_to_xy(size, to);
+    let half = size as i16 / 2;
+    let shorter = |delta: i16| match delta {
+        _ if !wrap => delta,
+        delta if delta > half => delta - size as i16,
+        delta if delta < -half => delta + size as i16,
+        delta => delta,
+    };
+    (shorter(tx - fx), shorter(ty - fy))
+}
+
+fn mirror_index(size: usize, index: u16) -> u16 {
+    let (x, y) = index_to_xy(size, index);
+    let mirrored_x = (size as i16 - 1) - x;
+    xy_to_index(size, mirrored_x, y).unwrap_or(index)
+}
+
+fn index_to_xy(size: usize, index: u16) -> (i16, i16) {
+    let x = (index as usize % size) as i16;
+    let y = (index as usize / size) as i16;
+    (x, y)
+}
+
+fn xy_to_index(size: usize, x: i16, y: i16) -> Option<u16> {
+    if x < 0 || y < 0 || x >= size as i16 || y >= size as i16 {
+        return None;
+    }
+    Some((y as usize * size + x as usize) as u16)
+}
+
+// Neighbouring cell; off the board is `None` unless `wrap`, which comes back
+// in on the opposite edge.
+fn step(size: usize, wrap: bool, index: u16, direction: Direction) -> Option<u16> {
+    let (x, y) = index_to_xy(size, index);
+    let (dx, dy) = match direction {
+        Direction::Up => (0, -1),
+        Direction::Right => (1, 0),
+        Direction::Down => (0, 1),
+        Direction::Left => (-1, 0),
+    };
+    if wrap {
+        let size = size as i16;
+        return xy_to_index(size as usize, (x + dx).rem_euclid(size), (y + dy).rem_euclid(size));
+    }
+    xy_to_index(size, x + dx, y + dy)
+}
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Down,
+        Direction::Right => Direction::Left,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+    }
+}
+
+fn turn_right(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Right,
+        Direction::Right => Direction::Down,
+        Direction::Down => Direction::Left,
+        Direction::Left => Direction::Up,
+    }
+}
+
+// Heading of the mirror image of a snake moving in `direction`.
+fn mirror_direction(direction: Direction) -> Direction {
+    match direction {
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+        vertical => vertical,
+    }
+}
+
+fn turn_left(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Left,
+        Direction::Right => Direction::Up,
+        Direction::Down => Direction::Right,
+        Direction::Left => Direction::Down,
+    }
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use super::*;
+
+    // A round account buffer sized for the largest board, 8-byte aligned like
+    // the real account data.
+    struct TestRound(Vec<u64>);
+
+    impl TestRound {
+        fn new() -> Self {
+            let len = 8 + round_space(MAX_BOARD_SIZE as u8);
+            Self(vec![0; len.div_ceil(8)])
+        }
+
+        fn bytes(&self) -> &[u8] {
+            bytemuck::cast_slice(&self.0)
+        }
+
+        fn data(&mut self) -> RoundData<'_> {
+            RoundData::from_account_data(bytemuck::cast_slice_mut(&mut self.0))
+                .expect("buffer fits every board size")
+        }
+
+        fn runtimes(&mut self) -> (SnakeRuntime<'_>, SnakeRuntime<'_>) {
+            let RoundData { round, alpha_board, beta_board, alpha_body, beta_body } = self.data();
+            (
+                SnakeRuntime::from_alpha(round, alpha_board, alpha_body),
+                SnakeRuntime::from_beta(round, beta_board, beta_body),
+            )
+        }
+
+        fn alpha_board(&self) -> Vec<u8> {
+            self.board_at(0)
+        }
+
+        fn beta_board(&self) -> Vec<u8> {
+            self.board_at(1)
+        }
+
+        fn board_at(&self, board: usize) -> Vec<u8> {
+            let cells = board_cells(self.board_size);
+            let start = 8 + std::mem::size_of::<Round>() + board * packed_board_len(cells);
+            unpack(&self.bytes()[start..start + packed_board_len(cells)], cells)
+        }
+    }
+
+    // A move log account buffer for rounds of up to `max_moves` ticks.
+    struct TestMoveLog(Vec<u64>);
+
+    impl TestMoveLog {
+        fn new(max_moves: u32) -> Self {
+            let len = 8 + move_log_space(max_moves, MAX_FOOD as u8);
+            Self(vec![0; len.div_ceil(8)])
+        }
+
+        fn data(&mut self) -> MoveLogData<'_> {
+            MoveLogData::from_account_data(bytemuck::cast_slice_mut(&mut self.0))
+                .expect("buffer holds the header")
+        }
+
+        fn header(&self) -> &MoveLog {
+            let bytes: &[u8] = bytemuck::cast_slice(&self.0);
+            bytemuck::from_bytes(&bytes[8..8 + std::mem::size_of::<MoveLog>()])
+        }
+
+        fn entries(&self) -> &[u8] {
+            let bytes: &[u8] = bytemuck::cast_slice(&self.0);
+            let start = 8 + std::mem::size_of::<MoveLog>();
+            &bytes[start..start + self.header().len as usize]
+        }
+    }
+
+    fn unpack(bytes: &[u8], cells: usize) -> Vec<u8> {
+        (0..cells as u16).map(|index| packed_cell(bytes, index)).collect()
+    }
+
+    fn cells_of(board: &Board) -> Vec<u8> {
+        unpack(board.bytes, board.len())
+    }
+
+    impl std::ops::Deref for TestRound {
+        type Target = Round;
+
+        fn deref(&self) -> &Round {
+            bytemuck::from_bytes(&self.bytes()[8..8 + std::mem::size_of::<Round>()])
+        }
+    }
+
+    impl std::ops::DerefMut for TestRound {
+        fn deref_mut(&mut self) -> &mut Round {
+            self.data().round
+        }
+    }
+
+    fn blank_round(round_id: u64, duration: i64) -> TestRound {
+        let (alpha_seed, beta_seed) =
+            derive_round_seeds(&hashv(&[&round_id.to_le_bytes()]).to_bytes());
+        let mut round = TestRound::new();
+        round.round_id = round_id;
+        round.status = RoundStatus::InProgress as u8;
+        round.alpha_seed = alpha_seed;
+        round.beta_seed = beta_seed;
+        round.alpha_alive = 1;
+        round.beta_alive = 1;
+        round.duration = duration;
+        round.alpha_dir = Direction::Right as u8;
+        round.beta_dir = Direction::Left as u8;
+        round.set_seed_source(SeedSource::Vrf {
+            oracle: Pubkey::default(),
+        });
+        round.seeded = 1;
+        round.rules_version = RulesVersion::CURRENT as u16;
+        round.set_setup(&setup(0, 1, RoundMode::Separate, 1));
+        round.min_bet = MIN_BET_LAMPORTS;
+        round.max_bet = MAX_BET_LAMPORTS;
+        round
+    }
+
+    fn setup(alpha_strategy: u8, beta_strategy: u8, mode: RoundMode, food_count: u8) -> RoundSetup {
+        RoundSetup {
+            alpha_strategy,
+            beta_strategy,
+            mode,
+            food_count,
+            food_odds: FoodOdds::default(),
+            map_id: 0,
+            rules: RoundRules::default(),
+        }
+    }
+
+    fn food_on(board: &[u8]) -> usize {
+        board.iter().filter(|cell| is_food(**cell)).count()
+    }
+
+    fn simulate_round(round_id: u64, duration: i64, setup: RoundSetup) -> AIChoice {
+        let mut round = blank_round(round_id, duration);
+        round.set_setup(&setup);
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+
+        let food_per_board = match setup.mode {
+            RoundMode::Separate => setup.food_count as usize,
+            RoundMode::Shared => 2 * setup.food_count as usize,
+        };
+
+        while round.winner().is_none() {
+            advance_round(&mut round.data()).expect("move should succeed");
+            assert_eq!(food_on(&round.alpha_board()), food_per_board);
+            assert_eq!(food_on(&round.beta_board()), food_per_board);
+            if setup.mode == RoundMode::Shared {
+                assert_eq!(round.alpha_board(), round.beta_board());
+            }
+        }
+
+        round.winner().unwrap()
+    }
+
+    fn tally(setup: RoundSetup, rounds: u64) -> (u32, u32, u32) {
+        let mut alpha = 0u32;
+        let mut beta = 0u32;
+        let mut draw = 0u32;
+
+        for round_id in 1..=rounds {
+            match simulate_round(round_id, 45, setup) {
+                AIChoice::Alpha => alpha += 1,
+                AIChoice::Beta => beta += 1,
+                AIChoice::Draw => draw += 1,
+            }
+        }
+
+        (alpha, beta, draw)
+    }
+
+    #[test]
+    fn fairness_snapshot_500_rounds() {
+        let (alpha, beta, draw) = tally(setup(0, 0, RoundMode::Separate, 1), 500);
+
+        println!(
+            "fairness snapshot => alpha: {alpha}, beta: {beta}, draw: {draw}"
+        );
+        assert_eq!(alpha + beta + draw, 500);
+    }
+
+    #[test]
+    fn greedy_vs_defensive_snapshot_500_rounds() {
+        let (alpha, beta, draw) = tally(setup(0, 1, RoundMode::Separate, 1), 500);
+
+        println!(
+            "greedy vs defensive => alpha: {alpha}, beta: {beta}, draw: {draw}"
+        );
+        assert_eq!(alpha + beta + draw, 500);
+    }
+
+    #[test]
+    fn strategy_matchup_matrix() {
+        assert!(strategy_by_id(STRATEGIES.len() as u8).is_none());
+
+        for alpha_strategy in 0..STRATEGIES.len() as u8 {
+            for beta_strategy in 0..STRATEGIES.len() as u8 {
+                let (alpha, beta, draw) =
+                    tally(setup(alpha_strategy, beta_strategy, RoundMode::Separate, 1), 20);
+                println!(
+                    "{alpha_strategy} vs {beta_strategy} => alpha: {alpha}, beta: {beta}, draw: {draw}"
+                );
+                assert_eq!(alpha + beta + draw, 20);
+            }
+        }
+    }
+
+    #[test]
+    fn vrf_randomness_drives_distinct_seeds() {
+        let (alpha_a, beta_a) = derive_round_seeds(&[7u8; 32]);
+        let (alpha_b, beta_b) = derive_round_seeds(&[8u8; 32]);
+
+        assert_ne!(alpha_a, beta_a);
+        assert_ne!(alpha_a, alpha_b);
+        assert_ne!(beta_a, beta_b);
+        assert_eq!(derive_round_seeds(&[7u8; 32]), (alpha_a, beta_a));
+    }
+
+    fn initialized_alpha(round: &mut TestRound) -> SnakeRuntime<'_> {
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        round.runtimes().0
+    }
+
+    #[test]
+    fn flood_fill_is_bounded_and_sees_leaving_tail() {
+        let mut round = blank_round(1, 60);
+        let mut snake = initialized_alpha(&mut round);
+        snake.board.fill(CELL_EMPTY);
+        snake.len = 0;
+        assert_eq!(reachable_area(&snake, 0) as usize, FLOOD_FILL_LIMIT);
+
+        // Wall off column 0 except for one body cell at (1, 5).
+        for y in 0..BOARD_SIZE as i16 {
+            snake.board.set(xy_to_index(BOARD_SIZE, 1, y).unwrap(), CELL_WALL);
+        }
+        let from = xy_to_index(BOARD_SIZE, 0, 5).unwrap();
+        let gate = xy_to_index(BOARD_SIZE, 1, 5).unwrap();
+        let elsewhere = [xy_to_index(BOARD_SIZE, 10, 10).unwrap(), xy_to_index(BOARD_SIZE, 11, 10).unwrap()];
+        snake.board.set(gate, CELL_BODY);
+        snake.tail = 0;
+        snake.len = 3;
+
+        // Gate is the head end of the body: still there when the fill arrives.
+        snake.body[..3].copy_from_slice(&[elsewhere[0], elsewhere[1], gate]);
+        assert_eq!(reachable_area(&snake, from) as usize, BOARD_SIZE);
+
+        // Gate is the tail: gone by the time the fill reaches it.
+        snake.body[..3].copy_from_slice(&[gate, elsewhere[0], elsewhere[1]]);
+        assert_eq!(reachable_area(&snake, from) as usize, FLOOD_FILL_LIMIT);
+    }
+
+    #[test]
+    fn path_search_is_bounded_on_the_largest_board() {
+        let mut round = blank_round(1, 60);
+        round.board_size = MAX_BOARD_SIZE as u8;
+        let mut snake = initialized_alpha(&mut round);
+        snake.board.fill(CELL_EMPTY);
+        let at = |x, y| xy_to_index(MAX_BOARD_SIZE, x, y).unwrap();
+        let far = at(31, 31);
+        let checks = std::cell::Cell::new(0usize);
+        let search = |snake: &SnakeRuntime, target: u16| {
+            checks.set(0);
+            let step = first_step_towards(snake, at(0, 0), |index| {
+                checks.set(checks.get() + 1);
+                index == target
+            });
+            (step, checks.get())
+        };
+
+        // An open 32x32 board with the target in the far corner: the capped
+        // search checks each neighbour of at most PATH_SEARCH_LIMIT cells and
+        // gives up, while the uncapped one walks the whole board.
+        let (capped, capped_checks) = search(&snake, far);
+        assert!(capped.is_none());
+        assert!(capped_checks <= 4 + 4 * PATH_SEARCH_LIMIT);
+        snake.capped_search = false;
+        let (uncapped, uncapped_checks) = search(&snake, far);
+        assert!(uncapped.is_some());
+        assert!(uncapped_checks >= MAX_BOARD_CELLS - 2);
+
+        // Targets inside the cap are found along the same shortest path.
+        snake.capped_search = true;
+        assert!(search(&snake, at(8, 6)).0 == Some(Direction::Right));
+        assert!(search(&snake, at(0, 9)).0 == Some(Direction::Down));
+    }
+
+    #[test]
+    fn snake_grows_past_old_decay_cap() {
+        let mut round = blank_round(2, 60);
+        let mut snake = initialized_alpha(&mut round);
+        snake.board.fill(CELL_EMPTY);
+        snake.head = xy_to_index(BOARD_SIZE, 0, 0).unwrap();
+        snake.body[0] = snake.head;
+        snake.tail = 0;
+        snake.len = 1;
+        snake.board.set(snake.head, CELL_HEAD);
+
+        for move_number in 1..=15 {
+            snake.food[0] = step(BOARD_SIZE, false, snake.head, Direction::Right).unwrap();
+            snake.board.set(snake.food[0], CELL_FOOD);
+            assert!(snake.apply_move(Some(Direction::Right), move_number));
+        }
+
+        assert!(snake.alive);
+        assert_eq!(snake.len, 16);
+        assert_eq!(cells_of(&snake.board).iter().filter(|cell| is_snake(**cell)).count(), 16);
+        assert_eq!(cells_of(&snake.board).iter().filter(|cell| **cell == CELL_HEAD).count(), 1);
+
+        // Without food the tail follows and the length holds.
+        assert!(!snake.apply_move(Some(Direction::Down), 16));
+        assert!(snake.alive);
+        assert_eq!(snake.len, 16);
+        assert_eq!(snake.tail_cell(), Some(xy_to_index(BOARD_SIZE, 1, 0).unwrap()));
+    }
+
+    #[test]
+    fn shared_board_snapshot_500_rounds() {
+        let (alpha, beta, draw) = tally(setup(0, 0, RoundMode::Shared, 1), 500);
+
+        println!("shared board => alpha: {alpha}, beta: {beta}, draw: {draw}");
+        assert_eq!(alpha + beta + draw, 500);
+    }
+
+    #[test]
+    fn multi_food_snapshot_500_rounds() {
+        let (alpha, beta, draw) = tally(setup(0, 1, RoundMode::Separate, 3), 500);
+        println!("3 food, greedy vs defensive => alpha: {alpha}, beta: {beta}, draw: {draw}");
+        assert_eq!(alpha + beta + draw, 500);
+
+        let (alpha, beta, draw) = tally(setup(0, 0, RoundMode::Shared, MAX_FOOD as u8), 100);
+        println!("{MAX_FOOD} food, shared board => alpha: {alpha}, beta: {beta}, draw: {draw}");
+        assert_eq!(alpha + beta + draw, 100);
+    }
+
+    #[test]
+    fn round_setup_rejects_bad_values() {
+        assert!(setup(0, 1, RoundMode::Separate, 1).validate().is_ok());
+        assert!(setup(0, STRATEGIES.len() as u8, RoundMode::Separate, 1).validate().is_err());
+        assert!(setup(0, 1, RoundMode::Separate, 0).validate().is_err());
+        assert!(setup(0, 1, RoundMode::Separate, MAX_FOOD as u8 + 1).validate().is_err());
+
+        let mut special = setup(0, 1, RoundMode::Separate, 1);
+        special.food_odds = FoodOdds { golden_pct: 50, poison_pct: 30, bonus_pct: 20, bonus_ttl: 10 };
+        assert!(special.validate().is_ok());
+        special.food_odds.bonus_pct = 21;
+        assert!(special.validate().is_err());
+        special.food_odds = FoodOdds { bonus_pct: 10, ..FoodOdds::default() };
+        assert!(special.validate().is_err());
+
+        let mut mapped = setup(0, 1, RoundMode::Separate, 1);
+        mapped.map_id = MAP_FROM_SEED;
+        assert!(mapped.validate().is_ok());
+        mapped.map_id = MAP_LAYOUTS.len() as u8;
+        assert!(mapped.validate().is_err());
+        mapped.map_id = 1;
+        mapped.rules.board_size = 16;
+        assert!(mapped.validate().is_err());
+
+        let defaults = setup(0, 1, RoundMode::Separate, 1);
+        for rules in [
+            RoundRules { max_moves: 0, ..RoundRules::default() },
+            RoundRules { max_moves: MAX_MOVES_CAP + 1, ..RoundRules::default() },
+            RoundRules { shrink_interval: 0, ..RoundRules::default() },
+            RoundRules { board_size: 8, ..RoundRules::default() },
+            RoundRules { tie_breaks: [1, 1, 0, 0, 0, 0], ..RoundRules::default() },
+            RoundRules { tie_breaks: [2, 1, 0, 0, 0, 0], ..RoundRules::default() },
+            RoundRules { tie_breaks: [3, 0, 0, 0, 0, 0], ..RoundRules::default() },
+            RoundRules { tie_breaks: [0, 1, 0, 0, 0, 0], ..RoundRules::default() },
+            RoundRules { tie_breaks: [1, MAX_TIE_BREAKS as u8 + 1, 0, 0, 0, 0], ..RoundRules::default() },
+        ] {
+            assert!(RoundSetup { rules, ..defaults }.validate().is_err());
+        }
+        let rules = RoundRules { tie_breaks: [1, 3, 2, 4, 5, 6], ..RoundRules::default() };
+        assert!(RoundSetup { rules, ..defaults }.validate().is_ok());
+    }
+
+    #[test]
+    fn template_params_check_duration_and_bet_limits() {
+        let params = TemplateParams {
+            setup: setup(0, 1, RoundMode::Separate, 1),
+            duration: 45,
+            min_bet: MIN_BET_LAMPORTS,
+            max_bet: MAX_BET_LAMPORTS,
+        };
+        assert!(params.validate().is_ok());
+        assert!(TemplateParams { duration: 0, ..params }.validate().is_err());
+        assert!(TemplateParams { min_bet: MIN_BET_LAMPORTS - 1, ..params }.validate().is_err());
+        assert!(TemplateParams { max_bet: MAX_BET_LAMPORTS + 1, ..params }.validate().is_err());
+        assert!(TemplateParams { min_bet: 2 * MIN_BET_LAMPORTS, max_bet: MIN_BET_LAMPORTS, ..params }
+            .validate()
+            .is_err());
+
+        let mut bad_setup = params;
+        bad_setup.setup.food_count = 0;
+        assert!(bad_setup.validate().is_err());
+
+        // The move log holds a longest entry for every tick, so the moves a
+        // round may run are bounded by the largest account create_round can
+        // allocate, and more food leaves room for fewer.
+        let mut long = params;
+        for food_count in 1..=MAX_FOOD as u8 {
+            let fitting = max_logged_moves(food_count);
+            assert!(8 + move_log_space(fitting, food_count) <= MAX_CPI_ACCOUNT_SPACE);
+            assert!(8 + move_log_space(fitting + 1, food_count) > MAX_CPI_ACCOUNT_SPACE);
+            long.setup.food_count = food_count;
+            long.setup.rules.max_moves = fitting;
+            assert!(long.validate().is_ok());
+            long.setup.rules.max_moves += 1;
+            assert!(long.validate().is_err());
+        }
+        // The limits `RoundRules::max_moves` documents.
+        assert_eq!([1, 2, 3, 4].map(max_logged_moves), [726, 390, 267, 202]);
+    }
+
+    #[test]
+    fn move_log_holds_a_longest_entry_every_tick() {
+        let max_moves = 40;
+        let mut log = TestMoveLog::new(max_moves);
+        let mut longest = LogEntry::new(0);
+        for _ in 0..2 {
+            for list in 0..2 {
+                for slot in 0..MAX_FOOD {
+                    longest.push_food(list, slot, CELL_FOOD, 0);
+                }
+            }
+        }
+        assert_eq!(longest.as_bytes().len(), MAX_LOG_ENTRY_LEN);
+
+        // Genesis plus every tick.
+        for _ in 0..=max_moves {
+            assert!(log.data().append(&longest));
+        }
+        assert_eq!(log.header().full, 0);
+        assert_eq!(log.entries().len(), (1 + max_moves as usize) * MAX_LOG_ENTRY_LEN);
+    }
+
+    #[test]
+    fn round_rules_drive_pacing() {
+        let rules = RoundRules {
+            max_moves: 40,
+            food_mirror_until: 10,
+            shrink_start: 20,
+            shrink_interval: 5,
+            ..RoundRules::default()
+        };
+        assert_eq!(max_round_moves(&rules, 45), 40);
+        assert!(should_use_mirrored_food(&rules, 10) && !should_use_mirrored_food(&rules, 11));
+        assert_eq!((shrink_level(&rules, 19), shrink_level(&rules, 20), shrink_level(&rules, 25)), (0, 1, 2));
+        assert_eq!(shrink_level(&rules, u32::MAX), BOARD_SIZE as i16 / 2 - 1);
+
+        let mut round = blank_round(4, 45);
+        round.set_rules(&rules);
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        while round.winner().is_none() {
+            advance_round(&mut round.data()).expect("move should succeed");
+        }
+        assert!(round.move_count <= rules.max_moves);
+    }
+
+    #[test]
+    fn batched_ticks_match_single_ticks_and_stop_at_resolution() {
+        let mut single = blank_round(10, 60);
+        let mut batched = blank_round(10, 60);
+        let mut log = TestMoveLog::new(MAX_MOVES_CAP);
+        initialize_round_state(&mut single.data()).expect("round init should succeed");
+        initialize_round_state(&mut batched.data()).expect("round init should succeed");
+
+        while single.winner().is_none() {
+            advance_round(&mut single.data()).expect("move should succeed");
+        }
+
+        let mut batches = 0;
+        loop {
+            let ticks =
+                advance_round_by(&mut batched.data(), &mut log.data(), MAX_MOVES_PER_BATCH, || u64::MAX)
+                    .expect("moves should succeed");
+            batches += 1;
+            if batched.winner().is_some() {
+                assert!((1..=MAX_MOVES_PER_BATCH).contains(&ticks));
+                break;
+            }
+            assert_eq!(ticks, MAX_MOVES_PER_BATCH);
+        }
+        assert!(batches == single.move_count.div_ceil(MAX_MOVES_PER_BATCH as u32));
+        assert_eq!(batched.bytes(), single.bytes());
+        assert_eq!(advance_round_by(&mut batched.data(), &mut log.data(), 1, || u64::MAX).unwrap(), 0);
+        assert_eq!(log.header().move_count, single.move_count);
+    }
+
+    #[test]
+    fn batches_stop_when_compute_runs_low() {
+        let mut round = blank_round(10, 60);
+        let mut log = TestMoveLog::new(MAX_MOVES_CAP);
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+
+        // Every tick is charged a full reserve: a budget of three and a half
+        // reserves leaves room to start four ticks.
+        let budget = std::cell::Cell::new(TICK_COMPUTE_RESERVE * 7 / 2);
+        let remaining = || {
+            let left = budget.get();
+            budget.set(left.saturating_sub(TICK_COMPUTE_RESERVE));
+            left
+        };
+        let ticks = advance_round_by(&mut round.data(), &mut log.data(), MAX_MOVES_PER_BATCH, remaining)
+            .expect("moves should succeed");
+        assert_eq!(ticks, 4);
+        assert_eq!(round.move_count, 4);
+
+        // The first tick runs even on a nearly spent budget.
+        let ticks = advance_round_by(&mut round.data(), &mut log.data(), MAX_MOVES_PER_BATCH, || 0)
+            .expect("moves should succeed");
+        assert_eq!(ticks, 1);
+        assert_eq!(log.header().move_count, 5);
+    }
+
+    // Rebuilds frames from a move log the way an off-chain client would: the
+    // map layout and round rules, the log header and its entries, nothing else.
+    struct Replay {
+        size: usize,
+        wrap: bool,
+        scatter_seed: u64,
+        shared: bool,
+        boards: [Vec<u8>; 2],
+        bodies: [std::collections::VecDeque<u16>; 2],
+        food: [[u16; MAX_FOOD]; 2],
+        alive: [bool; 2],
+        cells: [(u8, u8); 2],
+        keeps_tail: bool,
+    }
+
+    impl Replay {
+        fn new(log: &MoveLog) -> Self {
+            // Replay tools pick their rules by the log's version, like the engine.
+            // Spawns come from the header; only dying snakes' tails differ.
+            let version = RulesVersion::from_u16(log.rules_version).expect("no replay rules for this version");
+            let size = log.board_size as usize;
+            let shared = log.mode == RoundMode::Shared as u8;
+            let mut walls = vec![0u8; packed_board_len(size * size)];
+            let mut board = Board::new(&mut walls, size * size);
+            let offset = (size.saturating_sub(BOARD_SIZE) / 2) as i16;
+            draw_map(&mut board, size, offset, MAP_LAYOUTS[log.map_id as usize]);
+            let board = cells_of(&board);
+            let beta_cells = if shared { (CELL_BETA_BODY, CELL_BETA_HEAD) } else { (CELL_BODY, CELL_HEAD) };
+            let mut replay = Self {
+                size,
+                wrap: log.toroidal != 0,
+                scatter_seed: log.scatter_seed,
+                shared,
+                boards: [board.clone(), board],
+                bodies: Default::default(),
+                food: [[0; MAX_FOOD]; 2],
+                alive: [true; 2],
+                cells: [(CELL_BODY, CELL_HEAD), beta_cells],
+                keeps_tail: version >= RulesVersion::V6,
+            };
+            for (snake, head, dir) in [(0, log.alpha_head, log.alpha_dir), (1, log.beta_head, log.beta_dir)] {
+                let mut segment = head;
+                for _ in 0..INITIAL_SNAKE_LEN {
+                    replay.bodies[snake].push_front(segment);
+                    segment = step(size, false, segment, opposite(DIRECTIONS[dir as usize])).unwrap_or(segment);
+                }
+                let body: Vec<u16> = replay.bodies[snake].iter().copied().collect();
+                for cell in body {
+                    replay.set(snake, cell, replay.cells[snake].0);
+                }
+                replay.set(snake, head, replay.cells[snake].1);
+            }
+            replay
+        }
+
+        fn boards_of(&self, snake: usize) -> std::ops::Range<usize> {
+            if self.shared { 0..2 } else { snake..snake + 1 }
+        }
+
+        fn get(&self, snake: usize, cell: u16) -> u8 {
+            self.boards[snake][cell as usize]
+        }
+
+        fn set(&mut self, snake: usize, cell: u16, value: u8) {
+            for board in self.boards_of(snake) {
+                self.boards[board][cell as usize] = value;
+            }
+        }
+
+        fn pop_tail(&mut self, snake: usize) -> Option<u16> {
+            let tail = self.bodies[snake].pop_front()?;
+            if is_snake(self.get(snake, tail)) {
+                self.set(snake, tail, CELL_EMPTY);
+            }
+            Some(tail)
+        }
+
+        // Applies one entry; returns its length. The genesis entry has move 0.
+        fn apply(&mut self, entry: &[u8], move_number: u32, rules: &RoundRules) -> usize {
+            let mut next = [None; 2];
+            let mut moved = [false; 2];
+            for snake in 0..2 {
+                let nibble = entry[0] >> (4 * snake) & 0x0F;
+                let outcome = nibble >> 2;
+                let head = *self.bodies[snake].back().unwrap();
+                if move_number > 0 && outcome != MOVE_IDLE {
+                    next[snake] = step(self.size, self.wrap, head, DIRECTIONS[(nibble & 3) as usize]);
+                }
+                moved[snake] = matches!(outcome, MOVE_MOVED | MOVE_MOVED_THEN_DIED);
+                if move_number > 0 && outcome != MOVE_MOVED {
+                    self.alive[snake] = false;
+                }
+            }
+            let eaten = [0, 1].map(|snake| {
+                next[snake].map(|cell| self.get(snake, cell)).filter(|value| is_food(*value))
+            });
+            let mut vacated = [None; 2];
+            for snake in 0..2 {
+                if next[snake].is_some() && !grows_on(eaten[snake]) {
+                    vacated[snake] = self.pop_tail(snake);
+                }
+            }
+            for snake in 0..2 {
+                let Some(cell) = next[snake].filter(|_| moved[snake]) else {
+                    continue;
+                };
+                let head = *self.bodies[snake].back().unwrap();
+                if is_snake(self.get(snake, head)) {
+                    self.set(snake, head, self.cells[snake].0);
+                }
+                self.bodies[snake].push_back(cell);
+                self.set(snake, cell, self.cells[snake].1);
+                if eaten[snake] == Some(CELL_POISON) {
+                    for _ in 0..POISON_SHRINK {
+                        if self.bodies[snake].len() > 1 {
+                            self.pop_tail(snake);
+                        }
+                    }
+                }
+            }
+            for snake in 0..2 {
+                let Some(tail) = vacated[snake].filter(|_| self.keeps_tail && !moved[snake]) else {
+                    continue;
+                };
+                self.bodies[snake].push_front(tail);
+                if self.get(snake, tail) == CELL_EMPTY {
+                    let head = *self.bodies[snake].back().unwrap();
+                    let (body, head_cell) = self.cells[snake];
+                    self.set(snake, tail, if tail == head { head_cell } else { body });
+                }
+            }
+
+            let placements: Vec<(usize, usize, u8, u16)> = entry[2..2 + 3 * entry[1] as usize]
+                .chunks(3)
+                .map(|event| {
+                    let cell = u16::from_le_bytes([event[1], event[2]]);
+                    ((event[0] & 3) as usize, (event[0] >> 2 & 1) as usize, event[0] >> 4, cell)
+                })
+                .collect();
+            // In order, each replacing its slot's item; shared boards place a
+            // slot's two items as a pair, both old items going first.
+            for group in placements.chunks(if self.shared { 2 } else { 1 }) {
+                for (slot, list, _, _) in group {
+                    let old = self.food[*list][*slot];
+                    if is_food(self.get(*list, old)) {
+                        self.set(*list, old, CELL_EMPTY);
+                    }
+                }
+                for (slot, list, kind, cell) in group {
+                    self.set(*list, *cell, *kind);
+                    self.food[*list][*slot] = *cell;
+                }
+            }
+
+            let level = if move_number > 0 { shrink_level(rules, move_number) } else { 0 };
+            for index in 0..(self.size * self.size) as u16 {
+                if !is_in_shrunk_wall(rules, self.scatter_seed, index, level) {
+                    continue;
+                }
+                for board in &mut self.boards {
+                    if !(self.wrap && is_snake(board[index as usize])) {
+                        board[index as usize] = CELL_WALL;
+                    }
+                }
+            }
+            2 + 3 * entry[1] as usize
+        }
+    }
+
+    #[test]
+    fn move_log_replays_every_frame() {
+        let mut seeded_map = special_food_setup(RoundMode::Separate);
+        seeded_map.map_id = MAP_FROM_SEED;
+        let mut toroidal = special_food_setup(RoundMode::Shared);
+        toroidal.rules.toroidal = true;
+        let setups = [
+            setup(0, 1, RoundMode::Separate, 1),
+            seeded_map,
+            special_food_setup(RoundMode::Shared),
+            toroidal,
+        ];
+        for setup in setups {
+            for round_id in 1..=20 {
+                let mut round = blank_round(round_id, 45);
+                round.set_setup(&setup);
+                initialize_round_state(&mut round.data()).expect("round init should succeed");
+                let rules = round.rules();
+                let mut log = TestMoveLog::new(max_round_moves(&rules, round.duration));
+                {
+                    let header = log.data().log;
+                    header.board_size = round.board_size;
+                    header.mode = round.mode;
+                    header.round_id = round.round_id;
+                    header.food_count = round.food_count;
+                    header.toroidal = round.toroidal;
+                    header.rules_version = round.rules_version;
+                }
+
+                let mut replay = None;
+                let mut offset = 0;
+                while round.winner().is_none() {
+                    advance_round_logged(&mut round.data(), &mut log.data())
+                        .expect("move should succeed");
+                    let replay = replay.get_or_insert_with(|| {
+                        let mut replay = Replay::new(log.header());
+                        offset = replay.apply(log.entries(), 0, &rules);
+                        replay
+                    });
+                    offset += replay.apply(&log.entries()[offset..], round.move_count, &rules);
+                    assert_eq!(replay.boards[0], round.alpha_board(), "round {round_id}");
+                    assert_eq!(replay.boards[1], round.beta_board(), "round {round_id}");
+                    assert_eq!(replay.alive, [round.alpha_alive != 0, round.beta_alive != 0]);
+                }
+                assert_eq!(offset, log.entries().len());
+                assert_eq!(log.header().move_count, round.move_count);
+                assert_eq!(log.header().full, 0);
+            }
+        }
+    }
+
+    // A frame as a websocket client sees it: unpacked boards and scores.
+    struct Frame {
+        move_count: u32,
+        boards: [Vec<u8>; 2],
+        scores: [u32; 2],
+    }
+
+    fn frame_of(round: &TestRound) -> Frame {
+        Frame {
+            move_count: round.move_count,
+            boards: [round.alpha_board(), round.beta_board()],
+            scores: [round.alpha_score, round.beta_score],
+        }
+    }
+
+    // Recomputes the state hash chain from frames alone, packing each board the
+    // way the account stores it.
+    fn state_hash_chain(frames: &[Frame]) -> Vec<[u8; 32]> {
+        let pack = |cells: &[u8]| -> Vec<u8> {
+            cells.chunks(2).map(|pair| pair[0] | pair.get(1).unwrap_or(&0) << 4).collect()
+        };
+        let mut hash = [0u8; 32];
+        frames
+            .iter()
+            .map(|frame| {
+                hash = hashv(&[
+                    hash.as_ref(),
+                    &frame.move_count.to_le_bytes(),
+                    &pack(&frame.boards[0]),
+                    &pack(&frame.boards[1]),
+                    &frame.scores[0].to_le_bytes(),
+                    &frame.scores[1].to_le_bytes(),
+                ])
+                .to_bytes();
+                hash
+            })
+            .collect()
+    }
+
+    #[test]
+    fn state_hash_chain_matches_frames_and_finds_divergence() {
+        let mut round = blank_round(4, 60);
+        round.set_setup(&special_food_setup(RoundMode::Separate));
+        round.board_size = 15;
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        let mut frames = vec![frame_of(&round)];
+        let mut hashes = vec![round.state_hash];
+        while round.winner().is_none() {
+            advance_round(&mut round.data()).expect("move should succeed");
+            frames.push(frame_of(&round));
+            hashes.push(round.state_hash);
+        }
+        assert_eq!(state_hash_chain(&frames), hashes);
+
+        // A client whose copy of one frame is off sees every hash from that
+        // move on disagree with the committed chain.
+        let diverged = frames.len() / 2;
+        frames[diverged].scores[0] += 1;
+        let replayed = state_hash_chain(&frames);
+        let first_mismatch = replayed.iter().zip(&hashes).position(|(a, b)| a != b);
+        assert_eq!(first_mismatch, Some(diverged));
+        assert!(replayed[diverged..].iter().zip(&hashes[diverged..]).all(|(a, b)| a != b));
+    }
+
+    // Digest of the final state hashes of fixed rounds across strategies,
+    // modes, maps, board sizes and food kinds, played under `version`. Every
+    // version changes how at least one of these games goes.
+    fn rules_version_digest(version: RulesVersion) -> String {
+        let mut seeded_map = special_food_setup(RoundMode::Separate);
+        seeded_map.map_id = MAP_FROM_SEED;
+        let large = |mut setup: RoundSetup| {
+            setup.rules.board_size = MAX_BOARD_SIZE as u8;
+            setup
+        };
+        let mut wrapped = large(setup(3, 4, RoundMode::Shared, 2));
+        wrapped.rules.toroidal = true;
+        // Mirrored heads on an even board always stand on cells of opposite
+        // colour, so they only meet head-on, where V6 returns both tails, on
+        // an odd one.
+        let mut odd = setup(2, 4, RoundMode::Shared, 1);
+        odd.rules.board_size = MAX_BOARD_SIZE as u8 - 1;
+        let setups = [
+            setup(0, 1, RoundMode::Separate, 1),
+            seeded_map,
+            special_food_setup(RoundMode::Shared),
+            large(setup(2, 3, RoundMode::Shared, 2)),
+            large(setup(4, 2, RoundMode::Separate, 3)),
+            wrapped,
+            odd,
+        ];
+        let mut digest = [0u8; 32];
+        for setup in setups {
+            for round_id in 1..=10 {
+                let mut round = blank_round(round_id, 45);
+                round.set_setup(&setup);
+                round.rules_version = version as u16;
+                initialize_round_state(&mut round.data()).expect("round init should succeed");
+                while round.winner().is_none() {
+                    advance_round(&mut round.data()).expect("move should succeed");
+                }
+                digest = hashv(&[digest.as_ref(), round.state_hash.as_ref()]).to_bytes();
+            }
+        }
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    // Pinned per version: a change that alters how an existing version plays
+    // fails here, and belongs in a new version instead. No two versions share
+    // a digest, so each one's change is played by at least one of the games.
+    #[test]
+    fn rules_versions_keep_playing_the_same() {
+        assert_eq!(
+            rules_version_digest(RulesVersion::V1),
+            "0c059d3ec42f4f08c58b4f8c5eb6056810356b77280007afb6b62712289198cf"
+        );
+        assert_eq!(
+            rules_version_digest(RulesVersion::V2),
+            "0702290b7747bb634d42ca5ee767e998fdcf3a7d5578c95aa50a96248b41dcfd"
+        );
+        assert_eq!(
+            rules_version_digest(RulesVersion::V3),
+            "8620a4c619f4142a7fdacab71ea4861c92002c6d4e4faf98477f91ca29df27f8"
+        );
+        assert_eq!(
+            rules_version_digest(RulesVersion::V4),
+            "5f5e204cee3af3a514c38ee6448d6d44a4cb57c035b98194f788c3bef72b8a7d"
+        );
+        assert_eq!(
+            rules_version_digest(RulesVersion::V5),
+            "9910aa89639f035df6d39884446655eeaddd5f870f7beb60363fe2bbaa668093"
+        );
+        assert_eq!(
+            rules_version_digest(RulesVersion::V6),
+            "589bcbc1fc5d4dde1432ddd30998fab6f8659d98e3a18ee3ec584fd6c5b45cd5"
+        );
+        assert_eq!(
+            rules_version_digest(RulesVersion::V7),
+            "c878002d27866a5accc1b0238fd100e13836eb656c190c620c5d2a148d1d8f8f"
+        );
+
+        let mut round = blank_round(1, 45);
+        round.rules_version = 0;
+        assert!(initialize_round_state(&mut round.data()).is_err());
+        round.rules_version = RulesVersion::CURRENT as u16;
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        round.rules_version = RulesVersion::CURRENT as u16 + 1;
+        assert!(advance_round(&mut round.data()).is_err());
+        assert_eq!(round.move_count, 0);
+    }
+
+    // Pearson's statistic against a uniform expectation.
+    fn chi_squared(counts: &[u32]) -> f64 {
+        let expected = counts.iter().sum::<u32>() as f64 / counts.len() as f64;
+        counts.iter().map(|count| (*count as f64 - expected).powi(2) / expected).sum()
+    }
+
+    // Upper bound for a uniform chi-squared with `bins` - 1 degrees of freedom:
+    // mean plus about 3.5 standard deviations, so p < 0.001. Seeds are fixed,
+    // so these tests are deterministic either way.
+    fn chi_squared_limit(bins: usize) -> f64 {
+        let df = (bins - 1) as f64;
+        df + 3.5 * (2.0 * df).sqrt()
+    }
+
+    // Counts of consecutive pairs (a mod 10, b mod 10) of `below(400)` draws.
+    fn serial_pairs(rng: &mut Rng, draws: usize) -> Vec<u32> {
+        let mut counts = vec![0u32; 100];
+        let mut previous = rng.below(400) % 10;
+        for _ in 0..draws {
+            let next = rng.below(400) % 10;
+            counts[(previous * 10 + next) as usize] += 1;
+            previous = next;
+        }
+        counts
+    }
+
+    #[test]
+    fn food_placement_is_uniform_and_streams_are_independent() {
+        // Food placed through the engine lands on every cell equally often.
+        let mut round = blank_round(11, 60);
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        let (mut alpha, _) = round.runtimes();
+        alpha.board.fill(CELL_EMPTY);
+        let mut counts = vec![0u32; alpha.board.len()];
+        for move_number in 0..200_000 {
+            // Each respawn stands in for a tick, so start its journal afresh.
+            alpha.placed = FoodJournal::new();
+            respawn_food_single(&mut alpha, 0, move_number).expect("food should land");
+            counts[alpha.food[0] as usize] += 1;
+        }
+        let statistic = chi_squared(&counts);
+        assert!(statistic < chi_squared_limit(counts.len()), "placement chi-squared {statistic}");
+
+        // The LCG's lowest bit alternates, so `state % 400` alternates odd and
+        // even cells and half of all consecutive pairs never occur; the
+        // SplitMix64 streams pass the same serial test.
+        let legacy = chi_squared(&serial_pairs(&mut Rng::new(round.alpha_seed, true), 100_000));
+        assert!(legacy > 10.0 * chi_squared_limit(100), "LCG serial chi-squared {legacy}");
+        for seed in [round.alpha_seed, round.beta_seed, round.food_seed] {
+            let statistic = chi_squared(&serial_pairs(&mut Rng::new(seed, false), 100_000));
+            assert!(statistic < chi_squared_limit(100), "serial chi-squared {statistic}");
+        }
+
+        // Streams split off one round do not move together.
+        let mut streams = [round.alpha_seed, round.beta_seed, round.food_seed].map(|seed| Rng::new(seed, false));
+        assert!(streams[0].state != streams[1].state && streams[1].state != streams[2].state);
+        let mut joint = vec![0u32; 400];
+        for _ in 0..100_000 {
+            let alpha = streams[0].below(20);
+            let beta = streams[1].below(20);
+            joint[(alpha * 20 + beta) as usize] += 1;
+        }
+        let statistic = chi_squared(&joint);
+        assert!(statistic < chi_squared_limit(joint.len()), "joint chi-squared {statistic}");
+
+        // Mirrored food comes from the paired stream, so neither snake's own
+        // stream moves before the mirrored phase ends.
+        let mut round = blank_round(12, 60);
+        round.set_setup(&setup(0, 0, RoundMode::Separate, MAX_FOOD as u8));
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        let (alpha_seed, beta_seed, food_seed) = (round.alpha_seed, round.beta_seed, round.food_seed);
+        while round.move_count < round.food_mirror_until && round.winner().is_none() {
+            advance_round(&mut round.data()).expect("move should succeed");
+        }
+        assert_eq!((round.alpha_seed, round.beta_seed), (alpha_seed, beta_seed));
+        assert_ne!(round.food_seed, food_seed, "no food eaten in the mirrored phase");
+    }
+
+    fn chebyshev(size: usize, a: u16, b: u16) -> i16 {
+        let (dx, dy) = offset_between(size, false, a, b);
+        dx.abs().max(dy.abs())
+    }
+
+    // Walls every empty cell exactly `radius` steps (Chebyshev) from `head`,
+    // boxing the snake into the square around it.
+    fn box_in(board: &mut Board, head: u16, radius: i16) {
+        for cell in 0..board.len() as u16 {
+            if chebyshev(BOARD_SIZE, head, cell) == radius && !is_snake(board.get(cell)) {
+                board.set(cell, CELL_WALL);
+            }
+        }
+    }
+
+    #[test]
+    fn food_reach_is_filled_once_per_board_change_on_the_largest_board() {
+        let mut worst = setup(2, 3, RoundMode::Separate, MAX_FOOD as u8);
+        worst.food_odds = FoodOdds { bonus_pct: 100, bonus_ttl: 1, ..FoodOdds::default() };
+        worst.rules.board_size = MAX_BOARD_SIZE as u8;
+        worst.rules.shrink_start = 1;
+        let mut round = blank_round(31, 60);
+        round.set_setup(&worst);
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        let rules = round.rules();
+        let (mut alpha, mut beta) = round.runtimes();
+
+        // Every slot respawning on both boards in one tick shares one fill
+        // per board; placing food leaves it valid.
+        for slot in 0..MAX_FOOD {
+            respawn_symmetric_food(&mut alpha, &mut beta, slot, 1).expect("food should land");
+        }
+        for runtime in [&alpha, &beta] {
+            let cached = runtime.reach.expect("placements fill the reach");
+            assert_eq!(cached.0, fill_reach(runtime).0);
+        }
+
+        // The shrinking wall clears it, and the next placement refills it
+        // from the new board.
+        apply_shrink_to_runtime(&mut alpha, &rules, 31, 1).expect("shrink should succeed");
+        assert!(alpha.reach.is_none());
+        respawn_food_single(&mut alpha, 0, 1).expect("food should land");
+        assert_eq!(alpha.reach.expect("placement refills the reach").0, fill_reach(&alpha).0);
+        assert!(!alpha.reach.unwrap().contains(0), "the corner is behind the wall");
+
+        // Moves clear it too.
+        alpha.apply_move(alpha.choose_direction(strategy_by_id(2).unwrap()), 2);
+        assert!(alpha.reach.is_none());
+    }
+
+    #[test]
+    fn food_lands_where_the_snake_can_reach_it() {
+        let mut round = blank_round(21, 60);
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        let (mut alpha, _) = round.runtimes();
+        box_in(&mut alpha.board, alpha.head, 3);
+        for move_number in 0..200 {
+            alpha.placed = FoodJournal::new();
+            respawn_food_single(&mut alpha, 0, move_number).expect("food should land");
+            assert!(chebyshev(BOARD_SIZE, alpha.head, alpha.food[0]) < 3);
+        }
+        // Earlier versions place food anywhere, sealed off or not.
+        alpha.reachable_food = false;
+        let outside = (0..200)
+            .filter(|move_number| {
+                alpha.placed = FoodJournal::new();
+                respawn_food_single(&mut alpha, 0, *move_number).expect("food should land");
+                chebyshev(BOARD_SIZE, alpha.head, alpha.food[0]) > 3
+            })
+            .count();
+        assert!(outside > 150);
+
+        // With the pocket full, the fallback still places food, on the same
+        // cell for the same stream.
+        alpha.reachable_food = true;
+        clear_food_if_present(&mut alpha.board, alpha.food[0]);
+        for cell in 0..alpha.board.len() as u16 {
+            if chebyshev(BOARD_SIZE, alpha.head, cell) < 3 && alpha.board.get(cell) == CELL_EMPTY {
+                alpha.board.set(cell, CELL_WALL);
+            }
+        }
+        // Walls went up outside a tick, so the cached fill is stale.
+        alpha.reach = None;
+        let rng = alpha.rng;
+        respawn_food_single(&mut alpha, 0, 0).expect("fallback should place food");
+        let fallback = alpha.food[0];
+        assert!(chebyshev(BOARD_SIZE, alpha.head, fallback) > 3);
+        alpha.rng = rng;
+        respawn_food_single(&mut alpha, 0, 0).expect("fallback should place food");
+        assert_eq!(alpha.food[0], fallback);
+
+        // Mirrored food is checked on both boards: Alpha is boxed in, and on
+        // Beta's board the mirror of the top of Alpha's pocket is walled off.
+        let mut round = blank_round(22, 60);
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        let (mut alpha, mut beta) = round.runtimes();
+        box_in(&mut alpha.board, alpha.head, 3);
+        let (_, head_y) = index_to_xy(BOARD_SIZE, alpha.head);
+        for cell in 0..alpha.board.len() as u16 {
+            let mirrored = mirror_index(BOARD_SIZE, cell);
+            let top = index_to_xy(BOARD_SIZE, cell).1 < head_y;
+            if top && chebyshev(BOARD_SIZE, alpha.head, cell) < 3 && !is_snake(beta.board.get(mirrored)) {
+                beta.board.set(mirrored, CELL_WALL);
+            }
+        }
+        for move_number in 0..200 {
+            alpha.placed = FoodJournal::new();
+            beta.placed = FoodJournal::new();
+            respawn_symmetric_food(&mut alpha, &mut beta, 0, move_number).expect("food should land");
+            assert!(chebyshev(BOARD_SIZE, alpha.head, alpha.food[0]) < 3);
+            assert!(index_to_xy(BOARD_SIZE, alpha.food[0]).1 >= head_y);
+            assert_eq!(beta.food[0], mirror_index(BOARD_SIZE, alpha.food[0]));
+        }
+    }
+
+    #[test]
+    fn seed_slot_hash_is_the_first_block_from_the_seed_slot() {
+        // Slots 20, 18 and 15 produced blocks; 19, 17 and 16 were skipped.
+        let mut sysvar = 3u64.to_le_bytes().to_vec();
+        for slot in [20u64, 18, 15] {
+            sysvar.extend(slot.to_le_bytes());
+            sysvar.extend([slot as u8; 32]);
+        }
+        assert_eq!(slot_hash_at(&sysvar, 18), Some([18; 32]));
+        assert_eq!(slot_hash_at(&sysvar, 19), Some([20; 32]));
+        assert_eq!(slot_hash_at(&sysvar, 16), Some([18; 32]));
+        assert_eq!(slot_hash_at(&sysvar, 15), Some([15; 32]));
+        // Not produced yet, or older than every entry left.
+        assert_eq!(slot_hash_at(&sysvar, 21), None);
+        assert_eq!(slot_hash_at(&sysvar, 14), None);
+        assert_eq!(slot_hash_at(&sysvar[..48], 16), None);
+    }
+
+    #[test]
+    fn legacy_rounds_migrate_settled_or_voided() {
+        // A `round_v2` account as the Borsh layout wrote it, up to the fields
+        // the migration reads, plus a tail it ignores.
+        let legacy = |status: u8, winner: Option<u8>| {
+            let mut data = Round::DISCRIMINATOR.to_vec();
+            data.extend(7u64.to_le_bytes());
+            data.push(status);
+            data.extend(winner.map_or(vec![0], |choice| vec![1, choice]));
+            data.extend([CELL_WALL; 2 * LEGACY_BOARD_CELLS]);
+            data.extend([0; 16]); // alpha_seed, beta_seed
+            data.extend(4u32.to_le_bytes());
+            data.extend(2u32.to_le_bytes());
+            data.extend([1, 0]); // alpha_alive, beta_alive
+            data.extend(90u32.to_le_bytes());
+            data.extend(30_000_000u64.to_le_bytes());
+            data.extend(50_000_000u64.to_le_bytes());
+            data.extend(1_000i64.to_le_bytes());
+            data.extend([1]);
+            data.extend(1_045i64.to_le_bytes());
+            data.extend(45i64.to_le_bytes());
+            data.extend([0; 40]);
+            data
+        };
+        let migrate = |data: &[u8]| {
+            let mut round = TestRound::new();
+            migrate_round(&mut round, &LegacyRound::parse(data).unwrap(), 2_000);
+            round
+        };
+
+        let settled = migrate(&legacy(RoundStatus::Settled as u8, Some(AIChoice::Alpha as u8)));
+        assert!(settled.status == RoundStatus::Settled as u8);
+        assert!(settled.winner() == Some(AIChoice::Alpha));
+        assert_eq!((settled.round_id, settled.end_time, settled.duration), (7, 1_045, 45));
+        assert_eq!((settled.alpha_pool, settled.beta_pool), (30_000_000, 50_000_000));
+        assert_eq!((settled.alpha_score, settled.beta_score, settled.move_count), (4, 2, 90));
+        assert_eq!((settled.alpha_alive, settled.beta_alive), (1, 0));
+        assert_eq!(settled.board_size as usize, BOARD_SIZE);
+
+        // Rounds that never finished are voided so their bets can be refunded.
+        for status in [RoundStatus::Active, RoundStatus::InProgress] {
+            let voided = migrate(&legacy(status as u8, None));
+            assert!(voided.status == RoundStatus::Voided as u8);
+            assert!(voided.winner().is_none());
+            assert_eq!(voided.end_time, 2_000);
+            assert_eq!(voided.beta_pool, 50_000_000);
+        }
+
+        let mut foreign = legacy(RoundStatus::Settled as u8, None);
+        foreign[0] ^= 1;
+        assert!(LegacyRound::parse(&foreign).is_err());
+        assert!(LegacyRound::parse(&legacy(0, None)[..100]).is_err());
+    }
+
+    #[test]
+    fn tie_breaks_decide_in_the_rounds_order() {
+        let mut round = blank_round(1, 45);
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        let winner = |round: &mut TestRound, chain: &[TieBreak], force: bool| {
+            round.tie_breaks = [0; MAX_TIE_BREAKS];
+            for (slot, tie_break) in chain.iter().enumerate() {
+                round.tie_breaks[slot] = *tie_break as u8;
+            }
+            determine_winner(round, force)
+        };
+
+        // Both alive at the cap: Alpha leads on score, Beta is longer, ate
+        // more recently and sits closer to its food.
+        round.move_count = 80;
+        (round.alpha_score, round.beta_score) = (3, 2);
+        (round.alpha_len, round.beta_len) = (3, 5);
+        (round.alpha_last_meal, round.beta_last_meal) = (20, 70);
+        let at = |x, y| xy_to_index(BOARD_SIZE, x, y).unwrap();
+        (round.alpha_head, round.alpha_food[0]) = (at(2, 2), at(10, 10));
+        (round.beta_head, round.beta_food[0]) = (at(17, 17), at(17, 16));
+        assert!(winner(&mut round, &[], false).is_none());
+        assert!(winner(&mut round, &[], true) == Some(AIChoice::Alpha));
+        let survival_then = |criteria: &[TieBreak]| [&[TieBreak::Survival], criteria].concat();
+        assert!(winner(&mut round, &survival_then(&[TieBreak::Length]), true) == Some(AIChoice::Beta));
+        assert!(winner(&mut round, &survival_then(&[TieBreak::FoodDistance]), true) == Some(AIChoice::Beta));
+        assert!(winner(&mut round, &survival_then(&[TieBreak::MovesWithoutEating]), true) == Some(AIChoice::Beta));
+        assert!(
+            winner(&mut round, &survival_then(&[TieBreak::DeathMove, TieBreak::Score]), true) == Some(AIChoice::Alpha)
+        );
+        assert!(winner(&mut round, &survival_then(&[TieBreak::DeathMove]), true) == Some(AIChoice::Draw));
+        round.beta_score = 3;
+        assert!(winner(&mut round, &[], true) == Some(AIChoice::Draw));
+        assert!(winner(&mut round, &survival_then(&[TieBreak::Score, TieBreak::Length]), true) == Some(AIChoice::Beta));
+
+        // Alpha died first, still ahead on score: survival leads every chain,
+        // so the survivor wins whatever follows it.
+        round.alpha_score = 4;
+        (round.alpha_alive, round.alpha_death_move) = (0, 50);
+        assert!(winner(&mut round, &[], false) == Some(AIChoice::Beta));
+        assert!(winner(&mut round, &survival_then(&[TieBreak::Score]), false) == Some(AIChoice::Beta));
+
+        // Both dead; hunger counts to each snake's death.
+        (round.beta_alive, round.beta_death_move, round.beta_last_meal) = (0, 60, 50);
+        assert!(winner(&mut round, &survival_then(&[TieBreak::Score]), false) == Some(AIChoice::Alpha));
+        assert!(winner(&mut round, &survival_then(&[TieBreak::DeathMove]), false) == Some(AIChoice::Beta));
+        assert!(winner(&mut round, &survival_then(&[TieBreak::MovesWithoutEating]), false) == Some(AIChoice::Beta));
+        round.alpha_last_meal = 45;
+        assert!(winner(&mut round, &survival_then(&[TieBreak::MovesWithoutEating]), false) == Some(AIChoice::Alpha));
+
+        // The default chain goes score, then the later death.
+        assert!(winner(&mut round, &[], false) == Some(AIChoice::Alpha));
+        round.beta_score = 4;
+        assert!(winner(&mut round, &[], false) == Some(AIChoice::Beta));
+    }
+
+    #[test]
+    fn map_layouts_are_mirrored_open_and_playable() {
+        for map_id in 0..MAP_LAYOUTS.len() as u8 {
+            let mut round = blank_round(5, 60);
+            round.map_id = map_id;
+            initialize_round_state(&mut round.data()).expect("spawns must be clear of walls");
+
+            assert_mirrored_and_connected(&round);
+
+            let mut mapped = setup(0, 1, RoundMode::Shared, 2);
+            mapped.map_id = map_id;
+            let (alpha, beta, draw) = tally(mapped, 50);
+            println!("map {map_id}, shared board => alpha: {alpha}, beta: {beta}, draw: {draw}");
+            assert_eq!(alpha + beta + draw, 50);
+        }
+
+        let mut round = blank_round(6, 60);
+        round.map_id = MAP_FROM_SEED;
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        assert!((round.map_id as usize) < MAP_LAYOUTS.len());
+    }
+
+    // Walls are mirrored, food is not inside a wall, and no open cell is
+    // sealed off from the alpha spawn.
+    fn assert_mirrored_and_connected(round: &TestRound) {
+        let size = round.board_size as usize;
+        let board = &round.alpha_board();
+        assert_eq!(board.len(), size * size);
+        for index in 0..board.len() as u16 {
+            if board[index as usize] == CELL_WALL {
+                assert_eq!(board[mirror_index(size, index) as usize], CELL_WALL);
+            }
+        }
+        assert!(food_on(board) == 1 && board[round.alpha_food[0] as usize] != CELL_WALL);
+
+        let open = board.iter().filter(|cell| **cell != CELL_WALL).count();
+        let mut seen = vec![false; board.len()];
+        let mut stack = vec![round.alpha_head];
+        seen[round.alpha_head as usize] = true;
+        while let Some(cell) = stack.pop() {
+            for direction in DIRECTIONS {
+                if let Some(next) = step(size, false, cell, direction) {
+                    if !seen[next as usize] && board[next as usize] != CELL_WALL {
+                        seen[next as usize] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        let reached = seen.iter().filter(|seen| **seen).count();
+        assert_eq!(reached, open, "size {size}, map {}", round.map_id);
+    }
+
+    #[test]
+    fn board_sizes_from_min_to_max_are_playable() {
+        for board_size in [MIN_BOARD_SIZE, 15, BOARD_SIZE, 25, MAX_BOARD_SIZE] {
+            let board_size = board_size as u8;
+            let rules = RoundRules { board_size, ..RoundRules::default() };
+            for map_id in 0..MAP_LAYOUTS.len() as u8 {
+                let mut setup = setup(0, 1, RoundMode::Shared, 2);
+                setup.map_id = map_id;
+                setup.rules = rules;
+                if (board_size as usize) < BOARD_SIZE && map_id != 0 {
+                    assert!(setup.validate().is_err());
+                    continue;
+                }
+                setup.validate().expect("size and map should be accepted");
+
+                let mut round = blank_round(7, 60);
+                round.map_id = map_id;
+                round.set_rules(&rules);
+                initialize_round_state(&mut round.data()).expect("spawns must be clear of walls");
+                assert_mirrored_and_connected(&round);
+                assert_eq!(round.beta_head, mirror_index(board_size as usize, round.alpha_head));
+
+                let (alpha, beta, draw) = tally(setup, 10);
+                assert_eq!(alpha + beta + draw, 10);
+            }
+
+            let mut round = blank_round(8, 60);
+            round.set_rules(&rules);
+            round.map_id = MAP_FROM_SEED;
+            initialize_round_state(&mut round.data()).expect("round init should succeed");
+            if (board_size as usize) < BOARD_SIZE {
+                assert_eq!(round.map_id, 0);
+            }
+        }
+
+        let too_small = RoundRules { board_size: MIN_BOARD_SIZE as u8 - 1, ..RoundRules::default() };
+        let too_large = RoundRules { board_size: MAX_BOARD_SIZE as u8 + 1, ..RoundRules::default() };
+        assert!(too_small.validate().is_err() && too_large.validate().is_err());
+        assert!(round_space(MAX_BOARD_SIZE as u8) - round_space(BOARD_SIZE as u8) == 5 * (1024 - 400));
+    }
+
+    #[test]
+    fn seeded_spawns_are_mirrored_varied_and_clear_of_food() {
+        let mut openings = std::collections::HashSet::new();
+        for board_size in [MIN_BOARD_SIZE, BOARD_SIZE, MAX_BOARD_SIZE] {
+            let size = board_size;
+            let rules = RoundRules { board_size: board_size as u8, ..RoundRules::default() };
+            for mode in [RoundMode::Separate, RoundMode::Shared] {
+                for round_id in 1..=30 {
+                    let mut round = blank_round(round_id, 60);
+                    round.set_setup(&setup(0, 1, mode, MAX_FOOD as u8));
+                    round.set_rules(&rules);
+                    round.map_id = if size < BOARD_SIZE { 0 } else { MAP_FROM_SEED };
+                    initialize_round_state(&mut round.data()).expect("round init should succeed");
+
+                    let (alpha, beta) = round.runtimes();
+                    assert_eq!(beta.head, mirror_index(size, alpha.head));
+                    assert!(beta.dir == mirror_direction(alpha.dir));
+                    assert!(index_to_xy(size, alpha.head).0 < (size / 2) as i16 - 1);
+                    for segment in 0..INITIAL_SNAKE_LEN {
+                        assert_eq!(beta.segment(segment), mirror_index(size, alpha.segment(segment)));
+                    }
+                    let mut cell = alpha.head;
+                    for _ in 0..SPAWN_CLEAR_AHEAD {
+                        cell = step(size, false, cell, alpha.dir).expect("room ahead of the spawn");
+                        assert_eq!(alpha.board.get(cell), CELL_EMPTY);
+                    }
+                    for slot in 0..MAX_FOOD {
+                        let alpha_food = manhattan(size, false, alpha.head, alpha.food[slot]);
+                        assert_eq!(alpha_food, manhattan(size, false, beta.head, beta.food[slot]));
+                        assert!(!food_crowds_spawn(&alpha, &beta, slot, mode), "round {round_id}, slot {slot}");
+                    }
+                    openings.insert((size, alpha.head, alpha.dir as u8));
+                }
+            }
+        }
+        assert!(openings.len() > 60, "only {} distinct openings", openings.len());
+
+        // V1 rounds keep the fixed spawn.
+        let mut round = blank_round(3, 60);
+        round.rules_version = RulesVersion::V1 as u16;
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        assert_eq!(round.alpha_head, xy_to_index(BOARD_SIZE, 3, 10).unwrap());
+        assert_eq!((round.alpha_dir, round.beta_dir), (Direction::Right as u8, Direction::Left as u8));
+    }
+
+    #[test]
+    fn toroidal_boards_wrap_and_scatter_the_shrinking_wall() {
+        let size = BOARD_SIZE;
+        let corner = xy_to_index(size, 0, 0).unwrap();
+        let far_corner = xy_to_index(size, size as i16 - 1, size as i16 - 1).unwrap();
+        assert_eq!(step(size, false, corner, Direction::Left), None);
+        assert_eq!(step(size, true, corner, Direction::Left), xy_to_index(size, size as i16 - 1, 0));
+        assert_eq!(step(size, true, far_corner, Direction::Down), xy_to_index(size, size as i16 - 1, 0));
+        assert_eq!(manhattan(size, false, corner, far_corner), 2 * (size as u16 - 1));
+        assert_eq!(manhattan(size, true, corner, far_corner), 2);
+        let directions = preferred_food_directions(size, true, corner, far_corner);
+        assert!(directions == [Direction::Left, Direction::Up]);
+
+        let rules = RoundRules { toroidal: true, ..RoundRules::default() };
+        let max_level = shrink_level(&rules, u32::MAX);
+        let mut previous = vec![false; size * size];
+        for level in 1..=max_level {
+            let wall: Vec<bool> =
+                (0..(size * size) as u16).map(|index| is_in_shrunk_wall(&rules, 7, index, level)).collect();
+            for index in 0..(size * size) as u16 {
+                assert_eq!(wall[index as usize], wall[mirror_index(size, index) as usize]);
+                assert!(wall[index as usize] || !previous[index as usize], "level {level} lost a block");
+            }
+            // Scattered walls cover roughly what the ring would, not the ring.
+            let covered = wall.iter().filter(|wall| **wall).count() as f64;
+            let ring = (0..(size * size) as u16)
+                .filter(|index| is_in_shrunk_wall(&RoundRules::default(), 7, *index, level))
+                .count() as f64;
+            assert!((covered - ring).abs() <= ring * 0.25 + 8.0, "level {level}: {covered} vs {ring}");
+            if level == 1 {
+                let edge = wall[..size].iter().filter(|wall| **wall).count();
+                assert!(edge < size / 2, "the first level should not wall up the top edge");
+            }
+            previous = wall;
+        }
+        let other_round: Vec<bool> =
+            (0..(size * size) as u16).map(|index| is_in_shrunk_wall(&rules, 8, index, 3)).collect();
+        let this_round: Vec<bool> =
+            (0..(size * size) as u16).map(|index| is_in_shrunk_wall(&rules, 7, index, 3)).collect();
+        assert!(other_round != this_round);
+
+        // From V7 the pattern is split off the round seed, so the round id
+        // alone no longer gives it away before betting closes.
+        let mut first = blank_round(7, 60);
+        let mut second = blank_round(7, 60);
+        second.beta_seed ^= 1;
+        for round in [&mut first, &mut second] {
+            initialize_round_state(&mut round.data()).expect("round init should succeed");
+        }
+        assert!(first.scatter_seed() != second.scatter_seed() && first.scatter_seed() != 7);
+        first.rules_version = RulesVersion::V6 as u16;
+        assert_eq!(first.scatter_seed(), 7);
+
+        // Snakes cross the edges and scattered blocks never crush them: every
+        // death is a move into a wall or a body.
+        for (mode, label) in [(RoundMode::Separate, "separate boards"), (RoundMode::Shared, "shared board")] {
+            let mut setup = setup(0, 1, mode, 2);
+            setup.rules = RoundRules { shrink_start: 40, shrink_interval: 10, ..rules };
+            let mut crossed = false;
+            for round_id in 1..=20 {
+                let mut round = blank_round(round_id, 60);
+                round.set_setup(&setup);
+                initialize_round_state(&mut round.data()).expect("round init should succeed");
+                while round.winner().is_none() {
+                    let heads = [round.alpha_head, round.beta_head];
+                    let moves = advance_round(&mut round.data()).expect("move should succeed").directions;
+                    let after = [round.alpha_head, round.beta_head];
+                    for snake in 0..2 {
+                        if let Some(direction) = moves[snake].filter(|_| heads[snake] != after[snake]) {
+                            assert_eq!(step(size, true, heads[snake], direction), Some(after[snake]));
+                            crossed |= step(size, false, heads[snake], direction).is_none();
+                        }
+                    }
+                    let (alpha, beta) = round.runtimes();
+                    for snake in [&alpha, &beta] {
+                        assert!(!snake.alive || snake.board.get(snake.head) != CELL_WALL);
+                    }
+                }
+            }
+            assert!(crossed, "no snake crossed an edge on {label}");
+            let (alpha, beta, draw) = tally(setup, 100);
+            println!("toroidal, {label} => alpha: {alpha}, beta: {beta}, draw: {draw}");
+            assert_eq!(alpha + beta + draw, 100);
+        }
+    }
+
+    #[test]
+    fn packed_boards_keep_every_cell_value() {
+        let values = [
+            CELL_EMPTY, CELL_WALL, CELL_FOOD, CELL_BODY, CELL_BETA_BODY, CELL_HEAD,
+            CELL_BETA_HEAD, CELL_GOLDEN, CELL_POISON, CELL_BONUS,
+        ];
+        // 15 x 15 leaves an unused high nibble in the last byte.
+        let cells = board_cells(15);
+        let mut bytes = vec![0u8; packed_board_len(cells)];
+        let mut board = Board::new(&mut bytes, cells);
+        for index in 0..cells as u16 {
+            board.set(index, values[index as usize % values.len()]);
+        }
+        board.set(7, CELL_WALL);
+        board.set(7, CELL_EMPTY);
+        let expected: Vec<u8> = (0..cells).map(|index| values[index % values.len()]).collect();
+        assert_eq!(cells_of(&board)[..7], expected[..7]);
+        assert_eq!(board.get(7), CELL_EMPTY);
+        assert_eq!(cells_of(&board)[8..], expected[8..]);
+        assert_eq!(bytes.len(), 113);
+    }
+
+    fn special_food_setup(mode: RoundMode) -> RoundSetup {
+        let mut setup = setup(0, 1, mode, 2);
+        setup.food_odds = FoodOdds { golden_pct: 15, poison_pct: 20, bonus_pct: 15, bonus_ttl: 12 };
+        setup
+    }
+
+    #[test]
+    fn special_food_snapshot_500_rounds() {
+        let (alpha, beta, draw) = tally(special_food_setup(RoundMode::Separate), 500);
+        println!("special food, greedy vs defensive => alpha: {alpha}, beta: {beta}, draw: {draw}");
+        assert_eq!(alpha + beta + draw, 500);
+
+        let (alpha, beta, draw) = tally(special_food_setup(RoundMode::Shared), 100);
+        println!("special food, shared board => alpha: {alpha}, beta: {beta}, draw: {draw}");
+        assert_eq!(alpha + beta + draw, 100);
+    }
+
+    #[test]
+    fn golden_scores_extra_and_poison_shrinks() {
+        let mut round = blank_round(1, 60);
+        let mut snake = initialized_alpha(&mut round);
+        let dir = snake.dir;
+        let ahead = step(BOARD_SIZE, false, snake.head, dir).unwrap();
+
+        snake.board.set(ahead, CELL_GOLDEN);
+        assert!(snake.apply_move(Some(dir), 1));
+        assert_eq!((snake.score, snake.len), (GOLDEN_POINTS, INITIAL_SNAKE_LEN + 1));
+
+        let ahead = step(BOARD_SIZE, false, snake.head, dir).unwrap();
+        snake.board.set(ahead, CELL_POISON);
+        assert!(snake.apply_move(Some(dir), 2));
+        assert_eq!(snake.score, GOLDEN_POINTS - 1);
+        assert_eq!(snake.len, INITIAL_SNAKE_LEN + 1 - POISON_SHRINK);
+        assert_eq!(cells_of(&snake.board).iter().filter(|cell| is_snake(**cell)).count(), snake.len as usize);
+
+        // Strategies step around poison while another move is open.
+        let ahead = step(BOARD_SIZE, false, snake.head, dir).unwrap();
+        snake.board.set(ahead, CELL_POISON);
+        assert!(snake.choose_direction(&GreedyFood) != Some(dir));
+    }
+
+    #[test]
+    fn bonus_food_expires_and_respawns() {
+        let mut round = blank_round(9, 60);
+        round.set_food_odds(&FoodOdds { bonus_pct: 100, bonus_ttl: 3, ..FoodOdds::default() });
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        assert_eq!(round.alpha_board()[round.alpha_food[0] as usize], CELL_BONUS);
+        assert_eq!(round.alpha_food_expiry[0], 3);
+
+        for _ in 0..3 {
+            advance_round(&mut round.data()).expect("move should succeed");
+        }
+        // Whether eaten or expired, the slot now holds a fresh item.
+        assert!(round.alpha_food_expiry[0] > 3);
+        assert_eq!(food_on(&round.alpha_board()), 1);
+    }
+
+    // Alpha heads right from (x, 5), Beta heads left from (x, 5); bodies
+    // trail behind each head on an otherwise empty shared board.
+    fn facing_snakes(
+        round: &mut TestRound,
+        alpha_head_x: i16,
+        alpha_len: u16,
+        beta_head_x: i16,
+        beta_len: u16,
+    ) -> (SnakeRuntime<'_>, SnakeRuntime<'_>) {
+        *round = blank_round(3, 60);
+        round.mode = RoundMode::Shared as u8;
+        initialize_round_state(&mut round.data()).expect("round init should succeed");
+        let (mut alpha, mut beta) = round.runtimes();
+        let mut board = vec![CELL_EMPTY; BOARD_SIZE * BOARD_SIZE];
+
+        for (runtime, head_x, len, back) in [
+            (&mut alpha, alpha_head_x, alpha_len, -1i16),
+            (&mut beta, beta_head_x, beta_len, 1i16),
+        ] {
+            runtime.tail = 0;
+            runtime.len = 0;
+            for offset in (0..len as i16).rev() {
+                let cell = xy_to_index(BOARD_SIZE, head_x + back * offset, 5).unwrap();
+                runtime.push_head(cell);
+                board[cell as usize] = runtime.body_cell;
+            }
+            runtime.head = xy_to_index(BOARD_SIZE, head_x, 5).unwrap();
+            board[runtime.head as usize] = runtime.head_cell;
+        }
+        for (index, cell) in board.into_iter().enumerate() {
+            alpha.board.set(index as u16, cell);
+            beta.board.set(index as u16, cell);
+        }
+        (alpha, beta)
+    }
+
+    #[test]
+    fn shared_head_on_kills_the_shorter_snake() {
+        let mut round = TestRound::new();
+        let (mut alpha, mut beta) = facing_snakes(&mut round, 5, 3, 7, 4);
+        apply_shared_moves(&mut alpha, &mut beta, Some(Direction::Right), Some(Direction::Left), 1);
+        assert!(!alpha.alive);
+        assert!(beta.alive);
+        assert_eq!(beta.head, xy_to_index(BOARD_SIZE, 6, 5).unwrap());
+        assert_eq!(cells_of(&alpha.board), cells_of(&beta.board));
+
+        let (mut alpha, mut beta) = facing_snakes(&mut round, 5, 3, 7, 3);
+        apply_shared_moves(&mut alpha, &mut beta, Some(Direction::Right), Some(Direction::Left), 1);
+        assert!(!alpha.alive && !beta.alive);
+        assert_eq!(alpha.death_move, beta.death_move);
+    }
+
+    #[test]
+    fn shared_head_swap_kills_both() {
+        let mut round = TestRound::new();
+        let (mut alpha, mut beta) = facing_snakes(&mut round, 5, 3, 6, 5);
+        apply_shared_moves(&mut alpha, &mut beta, Some(Direction::Right), Some(Direction::Left), 1);
+        assert!(!alpha.alive && !beta.alive);
+    }
+
+    #[test]
+    fn shared_rival_body_blocks() {
+        let mut round = TestRound::new();
+        let (mut alpha, mut beta) = facing_snakes(&mut round, 5, 3, 12, 3);
+        // Beta's tail sits at (14, 5); park Alpha's next cell on Beta's neck.
+        alpha.head = xy_to_index(BOARD_SIZE, 13, 4).unwrap();
+        apply_shared_moves(&mut alpha, &mut beta, Some(Direction::Down), Some(Direction::Up), 1);
+        assert!(!alpha.alive);
+        assert!(beta.alive);
+    }
+
+    // Equal snakes end equally long however they die, so `TieBreak::Length`
+    // compares like with like.
+    #[test]
+    fn dying_snakes_keep_their_length() {
+        let mut round = TestRound::new();
+        let at = |x, y| xy_to_index(BOARD_SIZE, x, y).unwrap();
+
+        // Off the edge: no tail left on the fatal move.
+        let (mut alpha, _) = facing_snakes(&mut round, 19, 3, 12, 3);
+        assert!(!alpha.apply_move(Some(Direction::Right), 1));
+        assert!(!alpha.alive);
+        assert_eq!(alpha.len, 3);
+
+        // Into Beta's neck: the tail left before the collision and comes back.
+        // Up to V5 the snake died a segment short.
+        for keeps_tail in [true, false] {
+            let (mut alpha, _) = facing_snakes(&mut round, 5, 3, 12, 3);
+            alpha.keeps_tail = keeps_tail;
+            alpha.head = at(13, 4);
+            assert!(!alpha.apply_move(Some(Direction::Down), 1));
+            assert!(!alpha.alive);
+            assert_eq!(alpha.len, if keeps_tail { 3 } else { 2 });
+            assert_eq!(alpha.tail_cell() == Some(at(3, 5)), keeps_tail);
+            assert_eq!(alpha.board.get(at(3, 5)) == CELL_BODY, keeps_tail);
+        }
+
+        // Shared boards too, next to a rival that lives on.
+        let (mut alpha, mut beta) = facing_snakes(&mut round, 5, 3, 12, 3);
+        alpha.head = at(13, 4);
+        apply_shared_moves(&mut alpha, &mut beta, Some(Direction::Down), Some(Direction::Up), 1);
+        assert!(!alpha.alive && beta.alive);
+        assert_eq!((alpha.len, beta.len), (3, 3));
+        assert_eq!(alpha.board.get(at(3, 5)), CELL_BODY);
+        assert_eq!(cells_of(&alpha.board), cells_of(&beta.board));
+
+        // And a head-on between equals kills both at full length.
+        let (mut alpha, mut beta) = facing_snakes(&mut round, 5, 3, 7, 3);
+        apply_shared_moves(&mut alpha, &mut beta, Some(Direction::Right), Some(Direction::Left), 1);
+        assert!(!alpha.alive && !beta.alive);
+        assert_eq!((alpha.len, beta.len), (3, 3));
+        assert_eq!((alpha.board.get(at(3, 5)), beta.board.get(at(9, 5))), (CELL_BODY, CELL_BETA_BODY));
+        assert_eq!(cells_of(&alpha.board), cells_of(&beta.board));
+    }
+
+    #[test]
+    fn bettor_entropy_changes_round_seeds() {
+        let mut plain = blank_round(1, 60);
+        let mut mixed = blank_round(1, 60);
+        mixed.entropy_accumulator = [1u8; 32];
+
+        plain.seeded = 0;
+        mixed.seeded = 0;
+        seed_round(&mut plain.data(), &[7u8; 32]).unwrap();
+        seed_round(&mut mixed.data(), &[7u8; 32]).unwrap();
+
+        assert!(plain.seeded != 0 && mixed.seeded != 0);
+        assert_ne!(plain.alpha_seed, mixed.alpha_seed);
+        assert_ne!(plain.beta_seed, mixed.beta_seed);
     }
 }
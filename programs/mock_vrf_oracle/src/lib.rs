@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use solana_sha256_hasher::hashv;
+
+declare_id!("GskmsTtmpZunEVeP5weEVGsjr1HVXjjkqS1FTHuDn78K");
+
+// Local stand-in for a MagicBlock-style VRF oracle. Requests are queued by the
+// consumer program and fulfilled by anyone; randomness is NOT verifiable and
+// must never be used outside localnet/devnet testing.
+
+const IDENTITY_SEED: &[u8] = b"identity";
+const REQUEST_SEED: &[u8] = b"request";
+
+const MAX_CALLBACK_DISCRIMINATOR: usize = 8;
+const MAX_CALLBACK_ACCOUNTS: usize = 4;
+
+const REQUEST_SPACE: usize =
+    32 + 32 + 32 + (4 + MAX_CALLBACK_DISCRIMINATOR) + (4 + MAX_CALLBACK_ACCOUNTS * 33) + 8 + 1;
+
+#[program]
+pub mod mock_vrf_oracle {
+    use super::*;
+
+    pub fn request_randomness(
+        ctx: Context<RequestRandomness>,
+        caller_seed: [u8; 32],
+        callback_program_id: Pubkey,
+        callback_discriminator: Vec<u8>,
+        callback_accounts: Vec<CallbackAccountMeta>,
+    ) -> Result<()> {
+        require!(
+            callback_discriminator.len() <= MAX_CALLBACK_DISCRIMINATOR,
+            MockVrfError::CallbackTooLarge
+        );
+        require!(
+            callback_accounts.len() <= MAX_CALLBACK_ACCOUNTS,
+            MockVrfError::CallbackTooLarge
+        );
+
+        // Only the consumer program itself can sign for its identity PDA, so
+        // a request always comes from the program that receives the callback.
+        let (expected_identity, _) =
+            Pubkey::find_program_address(&[IDENTITY_SEED], &callback_program_id);
+        require_keys_eq!(
+            ctx.accounts.program_identity.key(),
+            expected_identity,
+            MockVrfError::InvalidCallerIdentity
+        );
+
+        let request = &mut ctx.accounts.request;
+        request.payer = ctx.accounts.payer.key();
+        request.caller_seed = caller_seed;
+        request.callback_program_id = callback_program_id;
+        request.callback_discriminator = callback_discriminator;
+        request.callback_accounts = callback_accounts;
+        request.requested_slot = Clock::get()?.slot;
+        request.bump = ctx.bumps.request;
+
+        Ok(())
+    }
+
+    pub fn fulfill_randomness<'info>(
+        ctx: Context<'_, '_, '_, 'info, FulfillRandomness<'info>>,
+    ) -> Result<()> {
+        let request = &ctx.accounts.request;
+        require_keys_eq!(
+            ctx.accounts.callback_program.key(),
+            request.callback_program_id,
+            MockVrfError::InvalidCallbackProgram
+        );
+
+        let clock = Clock::get()?;
+        let randomness = hashv(&[
+            &request.caller_seed,
+            &request.requested_slot.to_le_bytes(),
+            &clock.slot.to_le_bytes(),
+            &clock.unix_timestamp.to_le_bytes(),
+        ])
+        .to_bytes();
+
+        let mut data = request.callback_discriminator.clone();
+        data.extend_from_slice(&randomness);
+
+        let mut metas = Vec::with_capacity(request.callback_accounts.len() + 1);
+        let mut infos = Vec::with_capacity(request.callback_accounts.len() + 2);
+        metas.push(AccountMeta::new_readonly(
+            ctx.accounts.oracle_identity.key(),
+            true,
+        ));
+        infos.push(ctx.accounts.oracle_identity.to_account_info());
+
+        for meta in request.callback_accounts.iter() {
+            let info = ctx
+                .remaining_accounts
+                .iter()
+                .find(|account| account.key == &meta.pubkey)
+                .ok_or(MockVrfError::MissingCallbackAccount)?;
+            metas.push(if meta.is_writable {
+                AccountMeta::new(meta.pubkey, false)
+            } else {
+                AccountMeta::new_readonly(meta.pubkey, false)
+            });
+            infos.push(info.clone());
+        }
+        infos.push(ctx.accounts.callback_program.to_account_info());
+
+        invoke_signed(
+            &Instruction {
+                program_id: request.callback_program_id,
+                accounts: metas,
+                data,
+            },
+            &infos,
+            &[&[IDENTITY_SEED, &[ctx.bumps.oracle_identity]]],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(caller_seed: [u8; 32])]
+pub struct RequestRandomness<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub program_identity: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + REQUEST_SPACE,
+        seeds = [REQUEST_SEED, caller_seed.as_ref()],
+        bump
+    )]
+    pub request: Account<'info, RandomnessRequest>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FulfillRandomness<'info> {
+    pub authority: Signer<'info>,
+    /// CHECK: Receives the request rent back; must match the original payer.
+    #[account(mut, address = request.payer)]
+    pub payer: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [REQUEST_SEED, request.caller_seed.as_ref()],
+        bump = request.bump,
+        close = payer
+    )]
+    pub request: Account<'info, RandomnessRequest>,
+    /// CHECK: Oracle signer PDA presented to the callback program.
+    #[account(seeds = [IDENTITY_SEED], bump)]
+    pub oracle_identity: AccountInfo<'info>,
+    /// CHECK: Must match the callback program recorded on the request.
+    pub callback_program: AccountInfo<'info>,
+}
+
+#[account]
+pub struct RandomnessRequest {
+    pub payer: Pubkey,
+    pub caller_seed: [u8; 32],
+    pub callback_program_id: Pubkey,
+    pub callback_discriminator: Vec<u8>,
+    pub callback_accounts: Vec<CallbackAccountMeta>,
+    pub requested_slot: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CallbackAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+}
+
+#[error_code]
+pub enum MockVrfError {
+    #[msg("Caller identity does not match the callback program")]
+    InvalidCallerIdentity,
+    #[msg("Callback program does not match the request")]
+    InvalidCallbackProgram,
+    #[msg("Callback discriminator or account list is too large")]
+    CallbackTooLarge,
+    #[msg("Callback account missing from remaining accounts")]
+    MissingCallbackAccount,
+}
@@ -0,0 +1,37 @@
+// Moves every round left under the old `round_v2` seed to the current layout
+// with `migrate_legacy_round`, so its bets can be claimed or refunded. Run once
+// after upgrading the program, with the admin or agent wallet. Rounds still
+// delegated to the ER are skipped by the program; settle them first.
+import { loadEnv } from "../src/config/env";
+import { createL1Client } from "../src/chain/l1Client";
+import { migrateLegacyRound } from "../src/chain/methods";
+import { legacyRoundPda } from "../src/chain/pdas";
+
+// Discriminator plus the Borsh `ROUND_SPACE` of the legacy layout.
+const LEGACY_ROUND_ACCOUNT_LEN = 8 + 1200;
+
+async function main() {
+  const env = loadEnv();
+  const l1 = createL1Client(env.L1_RPC_URL, env.ANCHOR_WALLET);
+  const programId = l1.program.programId;
+
+  const accounts = await l1.connection.getProgramAccounts(programId, {
+    filters: [{ dataSize: LEGACY_ROUND_ACCOUNT_LEN }],
+  });
+  for (const { pubkey, account } of accounts) {
+    const roundId = account.data.readBigUInt64LE(8);
+    if (!legacyRoundPda(programId, roundId).equals(pubkey)) continue;
+
+    const sig = await migrateLegacyRound(
+      l1.program,
+      l1.wallet.publicKey,
+      roundId
+    );
+    console.log(`round ${roundId} migrated: ${sig}`);
+  }
+}
+
+main().catch((err) => {
+  console.error(err);
+  process.exit(1);
+});
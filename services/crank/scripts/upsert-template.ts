@@ -0,0 +1,47 @@
+// Creates or updates the round template the crank plays (`ROUND_TEMPLATE_ID`)
+// from the template settings in .env. Must be run with the admin wallet.
+import { loadEnv } from "../src/config/env";
+import { createL1Client } from "../src/chain/l1Client";
+import { upsertTemplate } from "../src/chain/methods";
+
+async function main() {
+  const env = loadEnv();
+  const l1 = createL1Client(env.L1_RPC_URL, env.ANCHOR_WALLET);
+
+  const sig = await upsertTemplate(
+    l1.program,
+    l1.wallet.publicKey,
+    env.ROUND_TEMPLATE_ID,
+    {
+      alphaStrategy: env.ALPHA_STRATEGY,
+      betaStrategy: env.BETA_STRATEGY,
+      mode: env.ROUND_MODE,
+      foodCount: env.FOOD_COUNT,
+      foodOdds: {
+        goldenPct: env.FOOD_GOLDEN_PCT,
+        poisonPct: env.FOOD_POISON_PCT,
+        bonusPct: env.FOOD_BONUS_PCT,
+        bonusTtl: env.FOOD_BONUS_TTL,
+      },
+      mapId: env.MAP_ID,
+      rules: {
+        maxMoves: env.MAX_ROUND_MOVES,
+        foodMirrorUntil: env.FOOD_MIRROR_UNTIL_MOVE,
+        shrinkStart: env.SHRINK_START_MOVE,
+        shrinkInterval: env.SHRINK_INTERVAL,
+        boardSize: env.BOARD_SIZE,
+        toroidal: env.TOROIDAL,
+        tieBreaks: env.TIE_BREAKS,
+      },
+      durationSeconds: env.ROUND_DURATION_SECONDS,
+      minBetLamports: env.MIN_BET_LAMPORTS,
+      maxBetLamports: env.MAX_BET_LAMPORTS,
+    }
+  );
+  console.log(`template ${env.ROUND_TEMPLATE_ID} saved: ${sig}`);
+}
+
+main().catch((err) => {
+  console.error(err);
+  process.exit(1);
+});
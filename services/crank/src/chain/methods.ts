@@ -1,6 +1,25 @@
 import { BN } from "@coral-xyz/anchor";
-import { PublicKey, SystemProgram } from "@solana/web3.js";
-import { betPda, configPda, roundPda, vaultPda, housePda } from "./pdas";
+import {
+  ComputeBudgetProgram,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_SLOT_HASHES_PUBKEY,
+} from "@solana/web3.js";
+import {
+  betPda,
+  configPda,
+  housePda,
+  legacyRoundPda,
+  moveLogPda,
+  programIdentityPda,
+  roundPda,
+  templatePda,
+  vaultPda,
+  vrfRequestPda,
+} from "./pdas";
+import { decodeMoveLog, decodeRound } from "./roundCodec";
+import { StrategyName, strategyId } from "./strategies";
+import { TieBreakName, tieBreakIds } from "./tieBreaks";
 
 const enumKey = (value: unknown): string | null => {
   if (typeof value === "string") return value;
@@ -21,6 +40,11 @@ const enumKey = (value: unknown): string | null => {
   return first;
 };
 
+export type SeedSourceArg =
+  | { vrf: { oracle: PublicKey } }
+  | { commitReveal: { commitment: number[] } }
+  | { bettorEntropy: {} };
+
 export async function fetchConfig(program: any) {
   const pda = configPda(program.programId);
   return program.account.config.fetch(pda);
@@ -28,7 +52,16 @@ export async function fetchConfig(program: any) {
 
 export async function fetchRound(program: any, roundId: bigint) {
   const pda = roundPda(program.programId, roundId);
-  return program.account.round.fetch(pda);
+  const info = await program.provider.connection.getAccountInfo(pda);
+  if (!info) throw new Error(`Account does not exist ${pda.toBase58()}`);
+  return decodeRound(program, info.data);
+}
+
+export async function fetchMoveLog(program: any, roundId: bigint) {
+  const pda = moveLogPda(program.programId, roundId);
+  const info = await program.provider.connection.getAccountInfo(pda);
+  if (!info) throw new Error(`Account does not exist ${pda.toBase58()}`);
+  return decodeMoveLog(program, info.data);
 }
 
 export async function fetchBetsForRound(program: any, roundId: bigint) {
@@ -38,18 +71,99 @@ export async function fetchBetsForRound(program: any, roundId: bigint) {
   );
 }
 
+// `map_id` value that lets the program draw the obstacle map from the round seed.
+export const MAP_FROM_SEED = 255;
+
+export type TemplateOptions = {
+  alphaStrategy: StrategyName;
+  betaStrategy: StrategyName;
+  mode: "separate" | "shared";
+  foodCount: number;
+  foodOdds: {
+    goldenPct: number;
+    poisonPct: number;
+    bonusPct: number;
+    bonusTtl: number;
+  };
+  mapId: number | "seed";
+  rules: {
+    maxMoves: number;
+    foodMirrorUntil: number;
+    shrinkStart: number;
+    shrinkInterval: number;
+    boardSize: number;
+    toroidal: boolean;
+    tieBreaks: TieBreakName[];
+  };
+  durationSeconds: number;
+  minBetLamports: number;
+  maxBetLamports: number;
+};
+
+function templateParams(options: TemplateOptions) {
+  return {
+    setup: {
+      alphaStrategy: strategyId(options.alphaStrategy),
+      betaStrategy: strategyId(options.betaStrategy),
+      mode: { [options.mode]: {} },
+      foodCount: options.foodCount,
+      foodOdds: options.foodOdds,
+      mapId: options.mapId === "seed" ? MAP_FROM_SEED : options.mapId,
+      rules: { ...options.rules, tieBreaks: tieBreakIds(options.rules.tieBreaks) },
+    },
+    duration: new BN(options.durationSeconds),
+    minBet: new BN(options.minBetLamports),
+    maxBet: new BN(options.maxBetLamports),
+  };
+}
+
+export async function fetchTemplate(program: any, templateId: number) {
+  return program.account.roundTemplate.fetchNullable(
+    templatePda(program.programId, templateId)
+  );
+}
+
+// Creates the template, or updates it in place if it already exists.
+export async function upsertTemplate(
+  program: any,
+  admin: PublicKey,
+  templateId: number,
+  options: TemplateOptions
+) {
+  const template = templatePda(program.programId, templateId);
+  const params = templateParams(options);
+  if (await fetchTemplate(program, templateId)) {
+    return program.methods
+      .updateTemplate(templateId, params)
+      .accountsPartial({ admin, config: configPda(program.programId), template })
+      .rpc();
+  }
+  return program.methods
+    .createTemplate(templateId, params)
+    .accountsPartial({
+      admin,
+      config: configPda(program.programId),
+      template,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
 export async function createRound(
   program: any,
   signer: PublicKey,
   roundId: bigint,
-  duration: number
+  templateId: number,
+  seedSource: SeedSourceArg
 ) {
   return program.methods
-    .createRound(new BN(roundId.toString()), new BN(duration))
+    .createRound(new BN(roundId.toString()), templateId, seedSource)
     .accountsPartial({
       signer,
       config: configPda(program.programId),
+      template: templatePda(program.programId, templateId),
       round: roundPda(program.programId, roundId),
+      moveLog: moveLogPda(program.programId, roundId),
       vault: vaultPda(program.programId, roundId),
       systemProgram: SystemProgram.programId,
     })
@@ -59,14 +173,84 @@ export async function createRound(
 export async function closeBetting(
   program: any,
   signer: PublicKey,
-  roundId: bigint
+  roundId: bigint,
+  vrfOracle: PublicKey | null
 ) {
+  const round = roundPda(program.programId, roundId);
   return program.methods
     .closeBetting(new BN(roundId.toString()))
     .accountsPartial({
       signer,
       config: configPda(program.programId),
+      round,
+      programIdentity: vrfOracle ? programIdentityPda(program.programId) : null,
+      oracleProgram: vrfOracle,
+      oracleRequest: vrfOracle ? vrfRequestPda(vrfOracle, round) : null,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
+export async function revealSeed(
+  program: any,
+  signer: PublicKey,
+  roundId: bigint,
+  secret: Buffer
+) {
+  return program.methods
+    .revealSeed(new BN(roundId.toString()), Array.from(secret))
+    .accountsPartial({
+      signer,
+      config: configPda(program.programId),
+      round: roundPda(program.programId, roundId),
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+    })
+    .rpc();
+}
+
+export async function seedFromEntropy(
+  program: any,
+  signer: PublicKey,
+  roundId: bigint
+) {
+  return program.methods
+    .seedFromEntropy(new BN(roundId.toString()))
+    .accountsPartial({
+      signer,
       round: roundPda(program.programId, roundId),
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+    })
+    .rpc();
+}
+
+export async function voidRound(
+  program: any,
+  signer: PublicKey,
+  roundId: bigint
+) {
+  return program.methods
+    .voidRound(new BN(roundId.toString()))
+    .accountsPartial({
+      signer,
+      round: roundPda(program.programId, roundId),
+    })
+    .rpc();
+}
+
+export async function refundBet(
+  program: any,
+  signer: PublicKey,
+  roundId: bigint,
+  user: PublicKey
+) {
+  return program.methods
+    .refundBet(new BN(roundId.toString()), user)
+    .accountsPartial({
+      signer,
+      round: roundPda(program.programId, roundId),
+      bet: betPda(program.programId, roundId, user),
+      vault: vaultPda(program.programId, roundId),
+      userAccount: user,
     })
     .rpc();
 }
@@ -84,6 +268,7 @@ export async function delegateRound(
       config: configPda(program.programId),
       round: roundPda(program.programId, roundId),
       roundPda: roundPda(program.programId, roundId),
+      moveLog: moveLogPda(program.programId, roundId),
     })
     .remainingAccounts([
       { pubkey: validator, isSigner: false, isWritable: false },
@@ -91,18 +276,34 @@ export async function delegateRound(
     .rpc();
 }
 
-export async function executeMove(
+// Ticks one execute_moves call may run (MAX_MOVES_PER_BATCH on-chain).
+export const MAX_MOVES_PER_TX = 16;
+// Batches ask for the whole transaction budget; the program decides how many
+// ticks fit in it.
+const BATCH_COMPUTE_UNITS = 1_400_000;
+
+// Runs up to `count` ticks in one transaction; the program stops early once the
+// round resolves or its compute runs low, and returns the ticks it ran as
+// return data.
+export async function executeMoves(
   program: any,
   signer: PublicKey,
-  roundId: bigint
+  roundId: bigint,
+  count: number
 ) {
   return program.methods
-    .executeMove(new BN(roundId.toString()))
+    .executeMoves(new BN(roundId.toString()), count)
     .accountsPartial({
       signer,
       config: configPda(program.programId),
       round: roundPda(program.programId, roundId),
+      moveLog: moveLogPda(program.programId, roundId),
     })
+    .preInstructions(
+      count > 1
+        ? [ComputeBudgetProgram.setComputeUnitLimit({ units: BATCH_COMPUTE_UNITS })]
+        : []
+    )
     .rpc();
 }
 
@@ -117,6 +318,7 @@ export async function settleAndUndelegate(
       payer,
       config: configPda(program.programId),
       round: roundPda(program.programId, roundId),
+      moveLog: moveLogPda(program.programId, roundId),
     })
     .rpc();
 }
@@ -138,6 +340,23 @@ export async function sweepVault(
     .rpc();
 }
 
+export async function migrateLegacyRound(
+  program: any,
+  signer: PublicKey,
+  roundId: bigint
+) {
+  return program.methods
+    .migrateLegacyRound(new BN(roundId.toString()))
+    .accountsPartial({
+      signer,
+      config: configPda(program.programId),
+      legacyRound: legacyRoundPda(program.programId, roundId),
+      round: roundPda(program.programId, roundId),
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
 export async function closeBet(
   program: any,
   signer: PublicKey,
@@ -167,6 +386,14 @@ export function getAiChoice(value: unknown): string {
   return "unknown";
 }
 
+export function isVoided(roundAccount: any): boolean {
+  return getRoundPhase(roundAccount).toLowerCase() === "voided";
+}
+
+export function isSeeded(roundAccount: any): boolean {
+  return Boolean(roundAccount.seeded);
+}
+
 export function hasWinner(roundAccount: any): boolean {
   return roundAccount.winner != null;
 }
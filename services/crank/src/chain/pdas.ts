@@ -2,9 +2,14 @@ import { PublicKey } from "@solana/web3.js";
 
 const CONFIG_SEED = "config_v2";
 const HOUSE_SEED = "house_v2";
-const ROUND_SEED = "round_v2";
+const ROUND_SEED = "round_v3";
+const LEGACY_ROUND_SEED = "round_v2";
 const BET_SEED = "bet_v2";
 const VAULT_SEED = "vault_v2";
+const TEMPLATE_SEED = "template";
+const MOVE_LOG_SEED = "move_log";
+const IDENTITY_SEED = "identity";
+const VRF_REQUEST_SEED = "request";
 
 export function configPda(programId: PublicKey): PublicKey {
   return PublicKey.findProgramAddressSync(
@@ -29,6 +34,27 @@ export function roundPda(programId: PublicKey, roundId: bigint): PublicKey {
   )[0];
 }
 
+export function legacyRoundPda(
+  programId: PublicKey,
+  roundId: bigint
+): PublicKey {
+  const le = Buffer.alloc(8);
+  le.writeBigUInt64LE(roundId);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(LEGACY_ROUND_SEED), le],
+    programId
+  )[0];
+}
+
+export function moveLogPda(programId: PublicKey, roundId: bigint): PublicKey {
+  const le = Buffer.alloc(8);
+  le.writeBigUInt64LE(roundId);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(MOVE_LOG_SEED), le],
+    programId
+  )[0];
+}
+
 export function vaultPda(programId: PublicKey, roundId: bigint): PublicKey {
   const le = Buffer.alloc(8);
   le.writeBigUInt64LE(roundId);
@@ -38,6 +64,32 @@ export function vaultPda(programId: PublicKey, roundId: bigint): PublicKey {
   )[0];
 }
 
+export function templatePda(programId: PublicKey, templateId: number): PublicKey {
+  const le = Buffer.alloc(4);
+  le.writeUInt32LE(templateId);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(TEMPLATE_SEED), le],
+    programId
+  )[0];
+}
+
+export function programIdentityPda(programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(IDENTITY_SEED)],
+    programId
+  )[0];
+}
+
+export function vrfRequestPda(
+  oracleProgramId: PublicKey,
+  round: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(VRF_REQUEST_SEED), round.toBuffer()],
+    oracleProgramId
+  )[0];
+}
+
 export function betPda(
   programId: PublicKey,
   roundId: bigint,
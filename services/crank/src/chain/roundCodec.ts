@@ -0,0 +1,180 @@
+import { PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
+
+// Round is a zero-copy account: a fixed header that Anchor decodes, followed by
+// alpha_board, beta_board (two cells per byte, see `unpackBoard`) and
+// alpha_body, beta_body (u16 LE per cell), each sized for the round's board. Enums, flags and options are
+// stored as integers; this module turns them back into the shapes the rest of
+// the crank uses ({ active: {} }, booleans, null for unset options).
+
+const DISCRIMINATOR_LEN = 8;
+const STATUSES = ["active", "inProgress", "settled", "voided"];
+const CHOICES = ["alpha", "beta", "draw"];
+const MODES = ["separate", "shared"];
+const DIRECTIONS = ["up", "right", "down", "left"];
+
+const variant = (names: string[], index: number) => ({
+  [names[index] ?? "unknown"]: {},
+});
+
+function seedSource(kind: number, param: number[]) {
+  if (kind === 0) return { vrf: { oracle: new PublicKey(Uint8Array.from(param)) } };
+  if (kind === 1) return { commitReveal: { commitment: param } };
+  return { bettorEntropy: {} };
+}
+
+const isZero = (value: any) => Number(value?.toString?.() ?? value) === 0;
+
+export type RoundBoards = {
+  alphaBoard: number[];
+  betaBoard: number[];
+  alphaBody: number[];
+  betaBody: number[];
+};
+
+// Boards store two cells per byte: even cells in the low nibble, odd cells in
+// the high nibble. Returns one number per cell, row-major.
+export function unpackBoard(bytes: Uint8Array, cells: number): number[] {
+  return Array.from({ length: cells }, (_, i) => (bytes[i >> 1] >> (4 * (i & 1))) & 0x0f);
+}
+
+export const packedBoardLen = (cells: number) => Math.ceil(cells / 2);
+
+// Inverse of `unpackBoard`; an odd cell count leaves the last high nibble 0.
+export function packBoard(board: number[]): Buffer {
+  const bytes = Buffer.alloc(packedBoardLen(board.length));
+  board.forEach((cell, i) => {
+    bytes[i >> 1] |= cell << (4 * (i & 1));
+  });
+  return bytes;
+}
+
+const u32le = (value: number) => {
+  const bytes = Buffer.alloc(4);
+  bytes.writeUInt32LE(value);
+  return bytes;
+};
+
+// Next link of Round.state_hash: sha256(previous hash, move count, both packed
+// boards, alpha and beta score), integers as u32 LE. Chaining it over the
+// frames of a round must reproduce the on-chain hash.
+export function frameHash(
+  previous: Uint8Array,
+  frame: {
+    moveCount: number;
+    alphaBoard: number[];
+    betaBoard: number[];
+    alphaScore: number;
+    betaScore: number;
+  }
+): Buffer {
+  return createHash("sha256")
+    .update(previous)
+    .update(u32le(frame.moveCount))
+    .update(packBoard(frame.alphaBoard))
+    .update(packBoard(frame.betaBoard))
+    .update(u32le(frame.alphaScore))
+    .update(u32le(frame.betaScore))
+    .digest();
+}
+
+// The regions fill the end of the account, which is sized exactly for them, so
+// they can be located without knowing the header length.
+export function decodeRoundBoards(data: Buffer, header: any): RoundBoards {
+  const cells = header.boardSize * header.boardSize;
+  const boardLen = packedBoardLen(cells);
+  const bodiesStart = data.length - 4 * cells;
+  const boardsStart = bodiesStart - 2 * boardLen;
+  const body = (start: number) =>
+    Array.from({ length: cells }, (_, i) => data.readUInt16LE(start + 2 * i));
+  return {
+    alphaBoard: unpackBoard(data.subarray(boardsStart, boardsStart + boardLen), cells),
+    betaBoard: unpackBoard(data.subarray(boardsStart + boardLen, bodiesStart), cells),
+    alphaBody: body(bodiesStart),
+    betaBody: body(bodiesStart + 2 * cells),
+  };
+}
+
+const MOVE_LOG_HEADER_LEN = 48;
+
+// Engine generations (Round.rules_version) whose move logs this decoder
+// understands. Replay tooling dispatches on the version and must refuse logs
+// from a newer engine rather than replay them under the wrong rules.
+export const KNOWN_RULES_VERSIONS = [1, 2, 3, 4, 5, 6, 7];
+const MOVE_OUTCOMES = ["idle", "moved", "crashed", "movedThenDied"];
+
+export type SnakeMove = { direction: string; outcome: string };
+export type FoodPlacement = {
+  list: "alpha" | "beta";
+  slot: number;
+  kind: number;
+  cell: number;
+};
+export type MoveLogEntry = {
+  alpha: SnakeMove;
+  beta: SnakeMove;
+  food: FoodPlacement[];
+};
+
+// MoveLog is zero-copy too: the header, then `len` bytes of entries, the
+// genesis entry (initial food) first and one per tick after it. Each entry is
+// a byte with a nibble per snake (direction in bits 0-1, outcome in bits 2-3,
+// alpha low), a placement count, then 3 bytes per food placement (slot bits
+// 0-1, list bit 2, kind bits 4-7; cell as u16 LE) in the order they were
+// made, so a slot can appear twice in one entry and the later one wins.
+export function decodeMoveLog(program: any, data: Buffer) {
+  const header = program.coder.accounts.decode("MoveLog", data);
+  if (!KNOWN_RULES_VERSIONS.includes(header.rulesVersion)) {
+    throw new Error(`unknown rules version ${header.rulesVersion}`);
+  }
+  const start = DISCRIMINATOR_LEN + MOVE_LOG_HEADER_LEN;
+  const entries: MoveLogEntry[] = [];
+  const snake = (nibble: number): SnakeMove => ({
+    direction: DIRECTIONS[nibble & 3],
+    outcome: MOVE_OUTCOMES[nibble >> 2],
+  });
+  for (let offset = start; offset < start + header.len; ) {
+    const food: FoodPlacement[] = [];
+    for (let i = 0; i < data[offset + 1]; i += 1) {
+      const at = offset + 2 + 3 * i;
+      food.push({
+        list: data[at] & 4 ? "beta" : "alpha",
+        slot: data[at] & 3,
+        kind: data[at] >> 4,
+        cell: data.readUInt16LE(at + 1),
+      });
+    }
+    entries.push({
+      alpha: snake(data[offset] & 0x0f),
+      beta: snake(data[offset] >> 4),
+      food,
+    });
+    offset += 2 + 3 * food.length;
+  }
+  return { ...header, full: header.full !== 0, entries };
+}
+
+export function decodeRound(program: any, data: Buffer) {
+  const header = program.coder.accounts.decode("Round", data);
+  const cells = header.boardSize * header.boardSize;
+  if (data.length < DISCRIMINATOR_LEN + 2 * packedBoardLen(cells) + 4 * cells) {
+    throw new Error("round account is shorter than its boards");
+  }
+  return {
+    ...header,
+    ...decodeRoundBoards(data, header),
+    status: variant(STATUSES, header.status),
+    winner: header.winner === 0 ? null : variant(CHOICES, header.winner - 1),
+    mode: variant(MODES, header.mode),
+    seedSource: seedSource(header.seedSource, header.seedParam),
+    seeded: header.seeded !== 0,
+    alphaAlive: header.alphaAlive !== 0,
+    betaAlive: header.betaAlive !== 0,
+    alphaDir: variant(DIRECTIONS, header.alphaDir),
+    betaDir: variant(DIRECTIONS, header.betaDir),
+    alphaDeathMove: header.alphaDeathMove === 0 ? null : header.alphaDeathMove,
+    betaDeathMove: header.betaDeathMove === 0 ? null : header.betaDeathMove,
+    endTime: isZero(header.endTime) ? null : header.endTime,
+    seedDeadline: isZero(header.seedDeadline) ? null : header.seedDeadline,
+  };
+}
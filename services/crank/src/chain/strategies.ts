@@ -0,0 +1,15 @@
+// Strategy ids registered on-chain (`STRATEGIES` in programs/magic_bet).
+// Append only: the index is what `create_round` stores on the round.
+export const STRATEGY_NAMES = [
+  "greedy_food",
+  "defensive",
+  "shortest_path",
+  "tail_follower",
+  "flood_fill",
+] as const;
+
+export type StrategyName = (typeof STRATEGY_NAMES)[number];
+
+export function strategyId(name: StrategyName): number {
+  return STRATEGY_NAMES.indexOf(name);
+}
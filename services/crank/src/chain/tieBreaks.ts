@@ -0,0 +1,19 @@
+// Tie-break criteria known on-chain (`TieBreak` in programs/magic_bet). The
+// on-chain id is the index + 1; 0 pads the end of a round's chain.
+export const TIE_BREAK_NAMES = [
+  "survival",
+  "score",
+  "length",
+  "death_move",
+  "food_distance",
+  "moves_without_eating",
+] as const;
+
+export type TieBreakName = (typeof TIE_BREAK_NAMES)[number];
+
+// `RoundRules.tie_breaks` for `names`; an empty list keeps the program's
+// default chain (survival, score, death_move).
+export function tieBreakIds(names: readonly TieBreakName[]): number[] {
+  const ids = names.map((name) => TIE_BREAK_NAMES.indexOf(name) + 1);
+  return [...ids, ...new Array(TIE_BREAK_NAMES.length - ids.length).fill(0)];
+}
@@ -1,8 +1,14 @@
 import dotenv from "dotenv";
 import { z } from "zod";
+import { STRATEGY_NAMES } from "../chain/strategies";
+import { TIE_BREAK_NAMES } from "../chain/tieBreaks";
 
 dotenv.config({ path: process.env.CRATE_ENV_PATH || ".env" });
 
+// Most moves a round may run per FOOD_COUNT, as the program's
+// `max_logged_moves` allows: the move log has to fit in one account.
+const MAX_LOGGED_MOVES = [726, 390, 267, 202];
+
 const EnvSchema = z.object({
   L1_RPC_URL: z.string().url(),
   ER_RPC_URL: z.string().url(),
@@ -10,8 +16,45 @@ const EnvSchema = z.object({
   PROGRAM_ID: z.string().min(32),
   ANCHOR_WALLET: z.string().default("~/.config/solana/id.json"),
   ER_VALIDATOR: z.string().min(32),
+  SEED_SOURCE: z.enum(["vrf", "commit_reveal", "bettor_entropy"]).default("bettor_entropy"),
+  VRF_ORACLE_PROGRAM_ID: z.string().min(32).optional(),
+  ROUND_TEMPLATE_ID: z.coerce.number().int().min(0).max(4294967295).default(0),
+  ALPHA_STRATEGY: z.enum(STRATEGY_NAMES).default("greedy_food"),
+  BETA_STRATEGY: z.enum(STRATEGY_NAMES).default("defensive"),
+  ROUND_MODE: z.enum(["separate", "shared"]).default("separate"),
+  FOOD_COUNT: z.coerce.number().int().min(1).max(4).default(1),
+  FOOD_GOLDEN_PCT: z.coerce.number().int().min(0).max(100).default(0),
+  FOOD_POISON_PCT: z.coerce.number().int().min(0).max(100).default(0),
+  FOOD_BONUS_PCT: z.coerce.number().int().min(0).max(100).default(0),
+  FOOD_BONUS_TTL: z.coerce.number().int().min(1).max(65535).default(20),
+  MAP_ID: z
+    .union([z.literal("seed"), z.coerce.number().int().min(0).max(254)])
+    .default(0),
+  MAX_ROUND_MOVES: z.coerce.number().int().min(1).max(MAX_LOGGED_MOVES[0]).default(300),
+  FOOD_MIRROR_UNTIL_MOVE: z.coerce.number().int().min(0).default(120),
+  SHRINK_START_MOVE: z.coerce.number().int().min(0).default(150),
+  SHRINK_INTERVAL: z.coerce.number().int().min(1).default(30),
+  BOARD_SIZE: z.coerce.number().int().min(10).max(32).default(20),
+  TOROIDAL: z
+    .enum(["true", "false"])
+    .default("false")
+    .transform((value) => value === "true"),
+  TIE_BREAKS: z
+    .string()
+    .default("")
+    .transform((value) => value.split(",").map((name) => name.trim()).filter(Boolean))
+    .pipe(
+      z
+        .array(z.enum(TIE_BREAK_NAMES))
+        .refine((names) => new Set(names).size === names.length, "duplicate tie-break")
+        .refine((names) => names.length === 0 || names[0] === "survival", "tie-breaks must start with survival")
+    ),
+  MIN_BET_LAMPORTS: z.coerce.number().int().positive().default(10000000),
+  MAX_BET_LAMPORTS: z.coerce.number().int().positive().default(1000000000),
+  SEED_FULFILL_TIMEOUT_MS: z.coerce.number().int().positive().default(30000),
   ROUND_DURATION_SECONDS: z.coerce.number().int().positive().default(45),
   MOVE_INTERVAL_MS: z.coerce.number().int().positive().default(100),
+  MOVES_PER_TX: z.coerce.number().int().min(1).max(16).default(1),
   MAX_MOVE_RETRIES: z.coerce.number().int().positive().default(5),
   MAX_STEP_RETRIES: z.coerce.number().int().positive().default(5),
   STUCK_ROUND_TIMEOUT_MS: z.coerce.number().int().positive().default(600000),
@@ -25,6 +68,9 @@ const EnvSchema = z.object({
     .enum(["fatal", "error", "warn", "info", "debug", "trace", "silent"])
     .default("info"),
   PORT: z.coerce.number().int().positive().default(8787),
+}).refine((env) => env.MAX_ROUND_MOVES <= MAX_LOGGED_MOVES[env.FOOD_COUNT - 1], {
+  message: "MAX_ROUND_MOVES is more than the move log holds at this FOOD_COUNT",
+  path: ["MAX_ROUND_MOVES"],
 });
 
 export type AppEnv = z.infer<typeof EnvSchema>;
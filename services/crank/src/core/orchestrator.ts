@@ -21,7 +21,7 @@ export class RoundOrchestrator {
       {
         nextRound: nextRound.toString(),
         lifecycle:
-          "create_round -> betting_open -> close_betting -> delegate_round -> game_loop -> settle_and_undelegate -> cleanup",
+          "create_round -> betting_open -> close_betting -> delegate_round -> game_loop -> settle_and_undelegate -> cleanup (void_round -> cleanup if the seed never arrives)",
       },
       "recovery snapshot loaded"
     );
@@ -59,14 +59,13 @@ export class RoundOrchestrator {
         return;
       }
       case "CREATE_ROUND": {
-        await withRetry(() => runCreateRound(this.ctx), {
+        const durationSeconds = await withRetry(() => runCreateRound(this.ctx), {
           attempts: this.ctx.env.MAX_STEP_RETRIES,
           baseDelayMs: 500,
           maxDelayMs: 5000,
         });
         this.ctx.store.markRoundCreated();
-        const deadline =
-          Date.now() + this.ctx.env.ROUND_DURATION_SECONDS * 1000;
+        const deadline = Date.now() + durationSeconds * 1000;
         this.ctx.store.setBettingDeadline(deadline);
         this.transitionTo("BETTING_OPEN");
         return;
@@ -91,12 +90,12 @@ export class RoundOrchestrator {
         return;
       }
       case "DELEGATE_ROUND": {
-        await withRetry(() => runDelegateRound(this.ctx), {
+        const delegated = await withRetry(() => runDelegateRound(this.ctx), {
           attempts: this.ctx.env.MAX_STEP_RETRIES,
           baseDelayMs: 500,
           maxDelayMs: 5000,
         });
-        this.transitionTo("GAME_LOOP");
+        this.transitionTo(delegated ? "GAME_LOOP" : "CLEANUP");
         return;
       }
       case "GAME_LOOP": {
@@ -3,9 +3,36 @@ import {
   fetchBetsForRound,
   fetchRound,
   getAiChoice,
+  isVoided,
+  refundBet,
   sweepVault,
 } from "../../chain/methods";
 
+async function refundVoidedRound(ctx: any, roundId: bigint) {
+  const bets = await fetchBetsForRound(ctx.l1.program, roundId);
+  for (const betEntry of bets) {
+    const user = betEntry.account.user;
+    if (!betEntry.account.claimed) {
+      const refundSig = await refundBet(
+        ctx.l1.program,
+        ctx.l1.wallet.publicKey,
+        roundId,
+        user
+      );
+      ctx.store.setLastTx(refundSig);
+    }
+    const sig = await closeBet(ctx.l1.program, ctx.l1.wallet.publicKey, roundId, user);
+    ctx.store.setLastTx(sig);
+  }
+
+  const sig = await sweepVault(ctx.l1.program, ctx.l1.wallet.publicKey, roundId);
+  ctx.store.setLastTx(sig);
+  ctx.log.info(
+    { roundId: roundId.toString(), refunded: bets.length, sig },
+    "voided round refunded and swept"
+  );
+}
+
 export async function runCleanup(ctx: any) {
   const stateBefore = ctx.store.get();
   const roundId = stateBefore.currentRoundId;
@@ -22,6 +49,12 @@ export async function runCleanup(ctx: any) {
   }
 
   const round = await fetchRound(ctx.l1.program, roundId);
+  if (isVoided(round)) {
+    await refundVoidedRound(ctx, roundId);
+    ctx.store.markCleanupCompleted(roundId);
+    return;
+  }
+
   const winner = getAiChoice(round.winner);
   if (winner === "unknown") {
     throw new Error(
@@ -1,17 +1,70 @@
-import { closeBetting, fetchRound } from "../../chain/methods";
+import { PublicKey } from "@solana/web3.js";
+import {
+  closeBetting,
+  fetchRound,
+  revealSeed,
+  seedFromEntropy,
+} from "../../chain/methods";
 import { serializeRoundState } from "../../ws/serializers";
 
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+// reveal_seed and seed_from_entropy read the hash of the round's seed slot, fixed at close_betting a
+// few slots ahead, so wait until the chain has produced it.
+async function waitForSeedSlot(ctx: any, roundId: bigint) {
+  const { seedSlot } = await fetchRound(ctx.l1.program, roundId);
+  while ((await ctx.l1.connection.getSlot("confirmed")) <= seedSlot.toNumber()) {
+    await sleep(400);
+  }
+}
+
 export async function runCloseBetting(ctx: any) {
   const roundId = ctx.store.get().currentRoundId;
   if (roundId == null) throw new Error("close_betting called with no round");
+  const vrfOracle =
+    ctx.env.SEED_SOURCE === "vrf"
+      ? new PublicKey(ctx.env.VRF_ORACLE_PROGRAM_ID)
+      : null;
   const sig = await closeBetting(
     ctx.l1.program,
     ctx.l1.wallet.publicKey,
-    roundId
+    roundId,
+    vrfOracle
   );
   ctx.store.setLastTx(sig);
   ctx.log.info({ roundId: roundId.toString(), sig }, "close_betting complete");
 
+  const secret = ctx.store.get().seedSecret;
+  if (ctx.env.SEED_SOURCE === "commit_reveal" && secret) {
+    await waitForSeedSlot(ctx, roundId);
+    const revealSig = await revealSeed(
+      ctx.l1.program,
+      ctx.l1.wallet.publicKey,
+      roundId,
+      secret
+    );
+    ctx.store.setLastTx(revealSig);
+    ctx.store.setSeedSecret(null);
+    ctx.log.info(
+      { roundId: roundId.toString(), sig: revealSig },
+      "reveal_seed complete"
+    );
+  }
+
+  if (ctx.env.SEED_SOURCE === "bettor_entropy") {
+    await waitForSeedSlot(ctx, roundId);
+    const seedSig = await seedFromEntropy(
+      ctx.l1.program,
+      ctx.l1.wallet.publicKey,
+      roundId
+    );
+    ctx.store.setLastTx(seedSig);
+    ctx.log.info(
+      { roundId: roundId.toString(), sig: seedSig },
+      "seed_from_entropy complete"
+    );
+  }
+
   const round = await fetchRound(ctx.l1.program, roundId);
   ctx.gateway?.publishRoundState(serializeRoundState(roundId, round));
 }
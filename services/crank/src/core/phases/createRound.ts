@@ -1,6 +1,33 @@
-import { createRound, fetchConfig, fetchRound } from "../../chain/methods";
+import { createHash, randomBytes } from "crypto";
+import { PublicKey } from "@solana/web3.js";
+import {
+  createRound,
+  fetchConfig,
+  fetchRound,
+  SeedSourceArg,
+} from "../../chain/methods";
 import { serializeRoundState } from "../../ws/serializers";
 
+function buildSeedSource(ctx: any): SeedSourceArg {
+  if (ctx.env.SEED_SOURCE === "commit_reveal") {
+    // Secret only lives in crank memory; a restart before reveal voids the round.
+    const secret = randomBytes(32);
+    ctx.store.setSeedSecret(secret);
+    const commitment = Array.from(createHash("sha256").update(secret).digest());
+    return { commitReveal: { commitment } };
+  }
+
+  ctx.store.setSeedSecret(null);
+  if (ctx.env.SEED_SOURCE === "bettor_entropy") {
+    return { bettorEntropy: {} };
+  }
+
+  if (!ctx.env.VRF_ORACLE_PROGRAM_ID) {
+    throw new Error("VRF_ORACLE_PROGRAM_ID is required when SEED_SOURCE=vrf");
+  }
+  return { vrf: { oracle: new PublicKey(ctx.env.VRF_ORACLE_PROGRAM_ID) } };
+}
+
 export async function runCreateRound(ctx: any) {
   const config = await fetchConfig(ctx.l1.program);
   const roundId = BigInt(config.roundId.toString());
@@ -8,7 +35,8 @@ export async function runCreateRound(ctx: any) {
     ctx.l1.program,
     ctx.l1.wallet.publicKey,
     roundId,
-    ctx.env.ROUND_DURATION_SECONDS
+    ctx.env.ROUND_TEMPLATE_ID,
+    buildSeedSource(ctx)
   );
   ctx.store.setRound(roundId);
   ctx.store.setLastTx(sig);
@@ -16,4 +44,6 @@ export async function runCreateRound(ctx: any) {
 
   const round = await fetchRound(ctx.l1.program, roundId);
   ctx.gateway?.publishRoundState(serializeRoundState(roundId, round));
+  // Betting stays open for the duration the template gave the round.
+  return Number(round.duration.toString());
 }
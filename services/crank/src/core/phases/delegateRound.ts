@@ -1,10 +1,47 @@
 import { PublicKey } from "@solana/web3.js";
-import { delegateRound, fetchRound } from "../../chain/methods";
+import {
+  delegateRound,
+  fetchRound,
+  isSeeded,
+  voidRound,
+} from "../../chain/methods";
 import { serializeRoundState } from "../../ws/serializers";
 
-export async function runDelegateRound(ctx: any) {
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+async function waitForSeed(ctx: any, roundId: bigint): Promise<boolean> {
+  const startedAt = Date.now();
+  while (true) {
+    const round = await fetchRound(ctx.l1.program, roundId);
+    if (isSeeded(round)) return true;
+
+    const deadline = round.seedDeadline
+      ? Number(round.seedDeadline.toString()) * 1000
+      : null;
+    if (deadline != null && Date.now() > deadline) return false;
+
+    if (Date.now() - startedAt > ctx.env.SEED_FULFILL_TIMEOUT_MS) {
+      throw new Error(`round ${roundId.toString()} seed not fulfilled yet`);
+    }
+    await sleep(500);
+  }
+}
+
+// Returns false when the seed never arrived and the round was voided instead.
+export async function runDelegateRound(ctx: any): Promise<boolean> {
   const roundId = ctx.store.get().currentRoundId;
   if (roundId == null) throw new Error("delegate_round called with no round");
+  if (!(await waitForSeed(ctx, roundId))) {
+    const sig = await voidRound(ctx.l1.program, ctx.l1.wallet.publicKey, roundId);
+    ctx.store.setLastTx(sig);
+    ctx.log.warn(
+      { roundId: roundId.toString(), sig },
+      "seed deadline passed, round voided"
+    );
+    const voided = await fetchRound(ctx.l1.program, roundId);
+    ctx.gateway?.publishRoundState(serializeRoundState(roundId, voided));
+    return false;
+  }
   const validator = new PublicKey(ctx.env.ER_VALIDATOR);
   const sig = await delegateRound(
     ctx.l1.program,
@@ -17,4 +54,5 @@ export async function runDelegateRound(ctx: any) {
 
   const round = await fetchRound(ctx.l1.program, roundId);
   ctx.gateway?.publishRoundState(serializeRoundState(roundId, round));
+  return true;
 }
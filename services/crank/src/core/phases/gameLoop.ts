@@ -1,8 +1,9 @@
 import {
-  executeMove,
+  executeMoves,
   fetchRound,
   getMoveCount,
   hasWinner,
+  MAX_MOVES_PER_TX,
 } from "../../chain/methods";
 import { serializeRoundState } from "../../ws/serializers";
 
@@ -12,7 +13,13 @@ export async function runGameLoop(ctx: any) {
   const roundId = ctx.store.get().currentRoundId;
   if (roundId == null) throw new Error("game_loop called with no round");
 
+  const interval = ctx.env.MOVE_INTERVAL_MS;
   const startedAt = Date.now();
+  // One execute_moves call of MOVES_PER_TX ticks is due every
+  // MOVE_INTERVAL_MS. When a slow transaction or RPC hiccup makes the loop miss
+  // intervals, their ticks are folded into the next call (up to
+  // MAX_MOVES_PER_TX) instead of being sent one transaction at a time.
+  let nextSendAt = startedAt;
   while (true) {
     const round = await fetchRound(ctx.er.program, roundId);
     ctx.gateway?.publishRoundState(serializeRoundState(roundId, round));
@@ -32,15 +39,30 @@ export async function runGameLoop(ctx: any) {
       return;
     }
 
-    const sig = await executeMove(
+    const intervals =
+      1 + Math.max(0, Math.floor((Date.now() - nextSendAt) / interval));
+    const count = Math.min(MAX_MOVES_PER_TX, ctx.env.MOVES_PER_TX * intervals);
+    const sig = await executeMoves(
       ctx.er.program,
       ctx.er.wallet.publicKey,
-      roundId
+      roundId,
+      count
     );
     ctx.store.setLastTx(sig);
+    nextSendAt += intervals * interval;
 
     const updatedRound = await fetchRound(ctx.er.program, roundId);
     ctx.gateway?.publishRoundState(serializeRoundState(roundId, updatedRound));
-    await sleep(ctx.env.MOVE_INTERVAL_MS);
+    if (count > 1) {
+      ctx.log.debug(
+        {
+          roundId: roundId.toString(),
+          requested: count,
+          ran: getMoveCount(updatedRound) - getMoveCount(round),
+        },
+        "batched moves"
+      );
+    }
+    await sleep(Math.max(0, nextSendAt - Date.now()));
   }
 }
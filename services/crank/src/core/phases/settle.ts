@@ -1,4 +1,9 @@
-import { fetchRound, settleAndUndelegate, getAiChoice } from "../../chain/methods";
+import {
+  fetchMoveLog,
+  fetchRound,
+  settleAndUndelegate,
+  getAiChoice,
+} from "../../chain/methods";
 import { serializeRoundState } from "../../ws/serializers";
 import { publishRoundResult } from "../../tapestry/content";
 
@@ -19,6 +24,20 @@ export async function runSettle(ctx: any) {
   const round = await fetchRound(ctx.l1.program, roundId);
   ctx.gateway?.publishRoundState(serializeRoundState(roundId, round));
 
+  // The move log is committed with the round; a full log can no longer replay
+  // the end of the game.
+  const moveLog = await fetchMoveLog(ctx.l1.program, roundId);
+  const logFields = {
+    roundId: roundId.toString(),
+    moves: moveLog.moveCount,
+    bytes: moveLog.len,
+  };
+  if (moveLog.full) {
+    ctx.log.warn(logFields, "move log filled up before the round ended");
+  } else {
+    ctx.log.debug(logFields, "move log committed");
+  }
+
   // Publish round result to Tapestry social layer (non-blocking)
   const winner = getAiChoice(round.winner);
   publishRoundResult(ctx.tapestry, ctx.l1.wallet.publicKey.toBase58(), {
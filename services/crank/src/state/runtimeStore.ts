@@ -20,6 +20,7 @@ export type RuntimeState = {
   gameEndedAtMs: number | null;
   settledAtMs: number | null;
   cleanupCompletedRoundId: bigint | null;
+  seedSecret: Buffer | null;
   retries: number;
   lastTx?: string;
 };
@@ -36,6 +37,7 @@ export class RuntimeStore {
     gameEndedAtMs: null,
     settledAtMs: null,
     cleanupCompletedRoundId: null,
+    seedSecret: null,
     retries: 0,
   };
 
@@ -89,6 +91,10 @@ export class RuntimeStore {
     this.state.cleanupCompletedRoundId = roundId;
   }
 
+  setSeedSecret(secret: Buffer | null) {
+    this.state.seedSecret = secret;
+  }
+
   setBettingDeadline(deadlineMs: number | null) {
     this.state.bettingDeadlineMs = deadlineMs;
   }
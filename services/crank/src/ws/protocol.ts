@@ -1,4 +1,9 @@
-export type RoundStatusWire = "Active" | "InProgress" | "Settled" | "Unknown";
+export type RoundStatusWire =
+  | "Active"
+  | "InProgress"
+  | "Settled"
+  | "Voided"
+  | "Unknown";
 export type WinnerWire = "Alpha" | "Beta" | "Draw" | null;
 
 export type RoundStateV1Event = {
@@ -14,6 +19,8 @@ export type RoundStateV1Event = {
   betaAlive: boolean;
   alphaBoard: number[];
   betaBoard: number[];
+  // Round.state_hash after this frame, hex.
+  stateHash: string;
 };
 
 export type RoundTransitionV1Event = {
@@ -7,6 +7,7 @@ function toRoundStatus(value: string): RoundStatusWire {
   if (normalized === "inprogress" || normalized === "in_progress")
     return "InProgress";
   if (normalized === "settled") return "Settled";
+  if (normalized === "voided") return "Voided";
   return "Unknown";
 }
 
@@ -48,5 +49,6 @@ export function serializeRoundState(
     betaAlive: Boolean(roundAccount.betaAlive),
     alphaBoard,
     betaBoard,
+    stateHash: Buffer.from(roundAccount.stateHash ?? []).toString("hex"),
   };
 }
@@ -1,35 +1,48 @@
 import * as anchor from "@coral-xyz/anchor";
 import { AnchorError, BN, Program, web3 } from "@coral-xyz/anchor";
 import { expect } from "chai";
+import { createHash, randomBytes } from "crypto";
 import {
   GetCommitmentSignature,
   MAGIC_CONTEXT_ID,
   MAGIC_PROGRAM_ID,
 } from "@magicblock-labs/ephemeral-rollups-sdk";
 import { MagicBet } from "../target/types/magic_bet";
+import { MockVrfOracle } from "../target/types/mock_vrf_oracle";
+import {
+  decodeMoveLog,
+  decodeRoundBoards,
+  frameHash,
+} from "../services/crank/src/chain/roundCodec";
 
 const DEFAULT_ER_VALIDATOR = "MAS1Dt9qreoRMQ14YQuhg8UTZMMzDdKhmkZMECCzk57";
 const LOCAL_VALIDATOR = "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev";
 
 const CONFIG_SEED = "config_v2";
 const HOUSE_SEED = "house_v2";
-const ROUND_SEED = "round_v2";
+const ROUND_SEED = "round_v3";
 const VAULT_SEED = "vault_v2";
+const TEMPLATE_SEED = "template";
+const MOVE_LOG_SEED = "move_log";
 const BET_SEED = "bet_v2";
+const IDENTITY_SEED = "identity";
+const VRF_REQUEST_SEED = "request";
 
 const MIN_BET = new BN(10_000_000); // 0.01 SOL
+const MAX_BET = new BN(1_000_000_000); // 1 SOL
 const BET_ALPHA = new BN(20_000_000); // 0.02 SOL
 const BET_BETA = new BN(30_000_000); // 0.03 SOL
 const INITIAL_HOUSE_FUND = new BN(3_000_000_000); // 3 SOL
 const HOUSE_MIN_BALANCE = 3_000_000_000;
+// Most compute units one execute_move may use on the rounds played here (20x20,
+// greedy vs defensive): half the default 200k per-instruction budget.
+const EXECUTE_MOVE_CU_CEILING = 100_000;
+// The program's TICK_COMPUTE_RESERVE: execute_moves only starts another tick
+// with this much budget left, so no tick, however costly, may use more.
+const TICK_COMPUTE_RESERVE = 150_000;
 
-const enumKey = (value: unknown): string | null => {
-  if (!value || typeof value !== "object") {
-    return null;
-  }
-  const keys = Object.keys(value as Record<string, unknown>);
-  return keys.length > 0 ? keys[0] : null;
-};
+const ROUND_STATUSES = ["active", "inProgress", "settled", "voided"];
+const AI_CHOICES = ["alpha", "beta", "draw"];
 
 const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
 
@@ -39,6 +52,8 @@ describe("magic_bet full integration", () => {
 
   const workspace = anchor.workspace as Record<string, Program<MagicBet>>;
   const program = workspace.magicBet ?? workspace.MagicBet;
+  const oracleWorkspace = anchor.workspace as Record<string, Program<MockVrfOracle>>;
+  const oracle = oracleWorkspace.mockVrfOracle ?? oracleWorkspace.MockVrfOracle;
 
   const adminWallet = provider.wallet as anchor.Wallet;
 
@@ -58,6 +73,9 @@ describe("magic_bet full integration", () => {
     provider.connection.rpcEndpoint.includes("localhost") ||
     provider.connection.rpcEndpoint.includes("127.0.0.1");
   const erOnly = isLocalnet ? it.skip : it;
+  // The mock oracle is only deployed to localnet, and magic_bet only takes VRF
+  // rounds in `mock-vrf` builds; other rounds seed from bettor entropy.
+  const mockVrfOnly = isLocalnet ? it : it.skip;
   let erValidator: web3.PublicKey | null = null;
 
   const [configPda] = web3.PublicKey.findProgramAddressSync(
@@ -75,12 +93,60 @@ describe("magic_bet full integration", () => {
       program.programId
     )[0];
 
+  // Round is zero-copy: Anchor decodes the header, and the packed boards and
+  // bodies fill the rest of the account. Boards go through the crank's decoder.
+  const fetchRound = async (target: Program<MagicBet>, roundId: BN) => {
+    const pda = roundPda(roundId);
+    const [header, info] = await Promise.all([
+      target.account.round.fetch(pda),
+      target.provider.connection.getAccountInfo(pda),
+    ]);
+    return {
+      ...header,
+      ...decodeRoundBoards(info!.data, header),
+      status: ROUND_STATUSES[header.status],
+      winner: header.winner === 0 ? null : AI_CHOICES[header.winner - 1],
+      seeded: header.seeded !== 0,
+    };
+  };
+
+  const moveLogPda = (roundId: BN) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MOVE_LOG_SEED), roundId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  const fetchMoveLog = async (target: Program<MagicBet>, roundId: BN) => {
+    const info = await target.provider.connection.getAccountInfo(moveLogPda(roundId));
+    return decodeMoveLog(target, info!.data);
+  };
+
   const vaultPda = (roundId: BN) =>
     web3.PublicKey.findProgramAddressSync(
       [Buffer.from(VAULT_SEED), roundId.toArrayLike(Buffer, "le", 8)],
       program.programId
     )[0];
 
+  const templatePda = (templateId: number) => {
+    const le = Buffer.alloc(4);
+    le.writeUInt32LE(templateId);
+    return web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(TEMPLATE_SEED), le],
+      program.programId
+    )[0];
+  };
+
+  const [programIdentityPda] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from(IDENTITY_SEED)],
+    program.programId
+  );
+
+  const oracleRequestPda = (roundId: BN) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(VRF_REQUEST_SEED), roundPda(roundId).toBuffer()],
+      oracle.programId
+    )[0];
+
   const betPda = (roundId: BN, user: web3.PublicKey) =>
     web3.PublicKey.findProgramAddressSync(
       [
@@ -153,14 +219,23 @@ describe("magic_bet full integration", () => {
     return undefined;
   };
 
+  // Program errors are asserted by name and resolved through the IDL, so
+  // appending a variant to MagicBetError never breaks these checks.
+  const errorCode = (name: string) => {
+    const code = program.idl.errors?.find((error) => error.name === name)?.code;
+    if (code === undefined) throw new Error(`unknown program error ${name}`);
+    return code;
+  };
+
   const expectCode = async (
     action: () => Promise<unknown>,
-    expectedCode: number,
+    expectedError: string,
     label: string
   ) => {
+    const expectedCode = errorCode(expectedError);
     try {
       await action();
-      expect.fail(`${label}: expected error code ${expectedCode}`);
+      expect.fail(`${label}: expected error ${expectedError}`);
     } catch (error) {
       let actual = parseErrorCode(error);
       const maybeGetLogs = (error as any)?.getLogs;
@@ -180,7 +255,7 @@ describe("magic_bet full integration", () => {
           }
         }
       }
-      expect(actual, `${label}: unexpected error code`).to.equal(expectedCode);
+      expect(actual, `${label}: expected ${expectedError}`).to.equal(expectedCode);
     }
   };
 
@@ -281,19 +356,108 @@ describe("magic_bet full integration", () => {
     return erValidator;
   };
 
-  const createRound = async (roundId: BN, durationSeconds: number) => {
+  type SeedSourceArg =
+    | { vrf: { oracle: web3.PublicKey } }
+    | { commitReveal: { commitment: number[] } }
+    | { bettorEntropy: {} };
+
+  type RoundSetupArg = {
+    alphaStrategy: number;
+    betaStrategy: number;
+    mode: { separate: {} } | { shared: {} };
+    foodCount: number;
+    foodOdds: {
+      goldenPct: number;
+      poisonPct: number;
+      bonusPct: number;
+      bonusTtl: number;
+    };
+    mapId: number;
+    rules: {
+      maxMoves: number;
+      foodMirrorUntil: number;
+      shrinkStart: number;
+      shrinkInterval: number;
+      boardSize: number;
+      toroidal: boolean;
+      tieBreaks: number[];
+    };
+  };
+
+  const DEFAULT_SETUP: RoundSetupArg = {
+    alphaStrategy: 0,
+    betaStrategy: 1,
+    mode: { separate: {} },
+    foodCount: 1,
+    foodOdds: { goldenPct: 0, poisonPct: 0, bonusPct: 0, bonusTtl: 0 },
+    mapId: 0,
+    rules: {
+      maxMoves: 300,
+      foodMirrorUntil: 120,
+      shrinkStart: 150,
+      shrinkInterval: 30,
+      boardSize: 20,
+      toroidal: false,
+      tieBreaks: [0, 0, 0, 0, 0, 0],
+    },
+  };
+
+  const templateParams = (
+    durationSeconds: number,
+    setup: Partial<RoundSetupArg> = {},
+    minBet = MIN_BET,
+    maxBet = MAX_BET
+  ) => ({
+    setup: { ...DEFAULT_SETUP, ...setup },
+    duration: new BN(durationSeconds),
+    minBet,
+    maxBet,
+  });
+
+  const createTemplate = async (
+    templateId: number,
+    params: ReturnType<typeof templateParams>,
+    admin: web3.Keypair | null = null
+  ) => {
+    const builder = program.methods.createTemplate(templateId, params).accountsPartial({
+      admin: admin ? admin.publicKey : adminWallet.publicKey,
+      config: configPda,
+      template: templatePda(templateId),
+      systemProgram: web3.SystemProgram.programId,
+    });
+    await (admin ? builder.signers([admin]) : builder).rpc();
+  };
+
+  const createRoundFromTemplate = async (
+    roundId: BN,
+    templateId: number,
+    seedSource: SeedSourceArg = { bettorEntropy: {} }
+  ) => {
     await program.methods
-      .createRound(roundId, new BN(durationSeconds))
+      .createRound(roundId, templateId, seedSource)
       .accountsPartial({
         signer: adminWallet.publicKey,
         config: configPda,
+        template: templatePda(templateId),
         round: roundPda(roundId),
+        moveLog: moveLogPda(roundId),
         vault: vaultPda(roundId),
         systemProgram: web3.SystemProgram.programId,
       })
       .rpc();
   };
 
+  // Each test round gets its own template, keyed by the round id.
+  const createRound = async (
+    roundId: BN,
+    durationSeconds: number,
+    seedSource: SeedSourceArg = { bettorEntropy: {} },
+    setup: Partial<RoundSetupArg> = {}
+  ) => {
+    await createTemplate(roundId.toNumber(), templateParams(durationSeconds, setup));
+    await createRoundFromTemplate(roundId, roundId.toNumber(), seedSource);
+  };
+
   const delegateRound = async (roundId: BN) => {
     const validator = await resolveErValidator();
     await program.methods
@@ -303,6 +467,7 @@ describe("magic_bet full integration", () => {
         config: configPda,
         round: roundPda(roundId),
         roundPda: roundPda(roundId),
+        moveLog: moveLogPda(roundId),
       })
       .remainingAccounts([
         {
@@ -322,10 +487,11 @@ describe("magic_bet full integration", () => {
     user: web3.PublicKey,
     amount: BN,
     choice: { alpha: {} } | { beta: {} } | { draw: {} },
-    signer?: web3.Keypair
+    signer?: web3.Keypair,
+    entropy: number[] | null = null
   ) => {
     const method = program.methods
-      .placeBet(roundId, choice, amount)
+      .placeBet(roundId, choice, amount, entropy)
       .accountsPartial({
         user,
         config: configPda,
@@ -342,35 +508,119 @@ describe("magic_bet full integration", () => {
     return method.rpc();
   };
 
-  const closeBettingL1 = async (roundId: BN) => {
+  const closeBettingL1 = async (roundId: BN, withOracle = false) => {
     await program.methods
       .closeBetting(roundId)
       .accountsPartial({
         signer: adminWallet.publicKey,
         config: configPda,
         round: roundPda(roundId),
+        programIdentity: withOracle ? programIdentityPda : null,
+        oracleProgram: withOracle ? oracle.programId : null,
+        oracleRequest: withOracle ? oracleRequestPda(roundId) : null,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+  };
+
+  const revealSeedL1 = async (roundId: BN, secret: Buffer) => {
+    await program.methods
+      .revealSeed(roundId, Array.from(secret))
+      .accountsPartial({
+        signer: adminWallet.publicKey,
+        config: configPda,
+        round: roundPda(roundId),
+        slotHashes: web3.SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .rpc();
+  };
+
+  const seedFromEntropyL1 = async (roundId: BN) => {
+    await program.methods
+      .seedFromEntropy(roundId)
+      .accountsPartial({
+        signer: adminWallet.publicKey,
+        round: roundPda(roundId),
+        slotHashes: web3.SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .rpc();
+  };
+
+  // reveal_seed and seed_from_entropy read the hash of the round's seed slot,
+  // fixed at close_betting a few slots ahead, so they only work once the chain
+  // has produced that slot.
+  const waitForSeedSlot = async (roundId: BN) => {
+    const { seedSlot } = await fetchRound(program, roundId);
+    while ((await provider.connection.getSlot("confirmed")) <= seedSlot.toNumber()) {
+      await sleep(400);
+    }
+  };
+
+  const seedRoundL1 = async (roundId: BN) => {
+    await waitForSeedSlot(roundId);
+    await seedFromEntropyL1(roundId);
+  };
+
+  // Stands in for the oracle network: the mock fulfills the pending request by
+  // invoking fulfill_round_seed on magic_bet with the oracle identity signer.
+  const fulfillSeedL1 = async (roundId: BN) => {
+    await oracle.methods
+      .fulfillRandomness()
+      .accountsPartial({
+        authority: adminWallet.publicKey,
+        payer: adminWallet.publicKey,
+        request: oracleRequestPda(roundId),
+        callbackProgram: program.programId,
       })
+      .remainingAccounts([
+        { pubkey: roundPda(roundId), isSigner: false, isWritable: true },
+      ])
       .rpc();
   };
 
-  const executeMovesEr = async (roundId: BN, maxMoves = 100) => {
+  // Records the compute units of every move, logs the peak and mean and fails
+  // if any move goes over EXECUTE_MOVE_CU_CEILING, so engine cost regressions
+  // break the test instead of only showing up in its output.
+  const executeMovesEr = async (
+    roundId: BN,
+    maxMoves = 100,
+    ceiling = EXECUTE_MOVE_CU_CEILING
+  ) => {
+    let peakUnits = 0;
+    let totalUnits = 0;
+    let moves = 0;
     for (let i = 0; i < maxMoves; i += 1) {
-      await erProgram.methods
+      const signature = await erProgram.methods
         .executeMove(roundId)
         .accountsPartial({
           signer: adminWallet.publicKey,
           config: configPda,
           round: roundPda(roundId),
+          moveLog: moveLogPda(roundId),
         })
         .rpc();
 
+      const tx = await erConnection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const units = tx?.meta?.computeUnitsConsumed;
+      expect(units, "execute_move compute units").to.be.a("number");
+      peakUnits = Math.max(peakUnits, units!);
+      totalUnits += units!;
+      moves += 1;
+
       if (i % 5 === 4) {
-        const roundState = await erProgram.account.round.fetch(roundPda(roundId));
+        const roundState = await fetchRound(erProgram, roundId);
         if (roundState.winner) {
-          return;
+          break;
         }
       }
     }
+    console.log(
+      `execute_move compute units over ${moves} moves: peak ${peakUnits}, mean ${Math.round(totalUnits / moves)}`
+    );
+    expect(peakUnits, "execute_move peak compute units").to.be.at.most(ceiling);
   };
 
   const settleEr = async (roundId: BN) => {
@@ -380,6 +630,7 @@ describe("magic_bet full integration", () => {
         payer: adminWallet.publicKey,
         config: configPda,
         round: roundPda(roundId),
+        moveLog: moveLogPda(roundId),
         magicProgram: MAGIC_PROGRAM_ID,
         magicContext: MAGIC_CONTEXT_ID,
       })
@@ -401,8 +652,8 @@ describe("magic_bet full integration", () => {
     }
 
     for (let i = 0; i < 30; i += 1) {
-      const state = await program.account.round.fetch(roundPda(roundId));
-      if (enumKey(state.status) === "settled") {
+      const state = await fetchRound(program, roundId);
+      if (state.status === "settled") {
         return state;
       }
       await sleep(1000);
@@ -471,10 +722,17 @@ describe("magic_bet full integration", () => {
     expect(instructionNames).to.include.members([
       normalize("initialize"),
       normalize("delegateAdmin"),
+      normalize("createTemplate"),
+      normalize("updateTemplate"),
+      normalize("retireTemplate"),
       normalize("createRound"),
       normalize("delegateRound"),
       normalize("placeBet"),
       normalize("closeBetting"),
+      normalize("fulfillRoundSeed"),
+      normalize("revealSeed"),
+      normalize("voidRound"),
+      normalize("refundBet"),
       normalize("executeMove"),
       normalize("settleAndUndelegate"),
       normalize("claimWinnings"),
@@ -498,11 +756,255 @@ describe("magic_bet full integration", () => {
           })
           .signers([attacker])
           .rpc(),
-      6000,
+      "Unauthorized",
       "delegate_admin unauthorized"
     );
   });
 
+  it("manages round templates and applies their bet limits", async function () {
+    this.timeout(120000);
+
+    const roundId = await nextRoundId();
+    const templateId = roundId.toNumber();
+    const attacker = web3.Keypair.generate();
+    await expectCode(
+      () => createTemplate(templateId, templateParams(30), attacker),
+      "Unauthorized",
+      "create_template unauthorized"
+    );
+    await expectCode(
+      () => createTemplate(templateId, templateParams(30, {}, MAX_BET, MIN_BET)),
+      "InvalidBetLimits",
+      "inverted bet limits"
+    );
+    await expectCode(
+      () =>
+        createTemplate(
+          templateId,
+          templateParams(30, { rules: { ...DEFAULT_SETUP.rules, tieBreaks: [1, 1, 0, 0, 0, 0] } })
+        ),
+      "InvalidTieBreaks",
+      "repeated tie-break"
+    );
+    await expectCode(
+      () =>
+        createTemplate(
+          templateId,
+          templateParams(30, { rules: { ...DEFAULT_SETUP.rules, tieBreaks: [2, 1, 0, 0, 0, 0] } })
+        ),
+      "InvalidTieBreaks",
+      "tie-breaks not led by survival"
+    );
+    await createTemplate(templateId, templateParams(30));
+
+    const minBet = MIN_BET.muln(2);
+    await program.methods
+      .updateTemplate(
+        templateId,
+        templateParams(
+          40,
+          {
+            betaStrategy: 4,
+            rules: { ...DEFAULT_SETUP.rules, toroidal: true, tieBreaks: [1, 3, 2, 0, 0, 0] },
+          },
+          minBet
+        )
+      )
+      .accountsPartial({
+        admin: adminWallet.publicKey,
+        config: configPda,
+        template: templatePda(templateId),
+      })
+      .rpc();
+
+    await createRoundFromTemplate(roundId, templateId);
+    const round = await fetchRound(program, roundId);
+    expect(round.templateId).to.equal(templateId);
+    expect(round.rulesVersion).to.equal(7);
+    expect(round.duration.toNumber()).to.equal(40);
+    expect(round.betaStrategy).to.equal(4);
+    expect(round.toroidal).to.equal(1);
+    expect((await fetchMoveLog(program, roundId)).toroidal).to.equal(1);
+    expect(round.tieBreaks).to.deep.equal([1, 3, 2, 0, 0, 0]);
+    expect(round.minBet.toString()).to.equal(minBet.toString());
+    await expectCode(
+      () => placeBetL1(roundId, adminWallet.publicKey, MIN_BET, { alpha: {} }),
+      "BetAmountTooLow",
+      "bet below template minimum"
+    );
+
+    await program.methods
+      .retireTemplate(templateId)
+      .accountsPartial({
+        admin: adminWallet.publicKey,
+        config: configPda,
+        template: templatePda(templateId),
+      })
+      .rpc();
+    const nextId = await nextRoundId();
+    await expectCode(
+      () => createRoundFromTemplate(nextId, templateId),
+      "TemplateRetired",
+      "retired template"
+    );
+  });
+
+  mockVrfOnly("seeds the board only after the VRF callback fires", async function () {
+    this.timeout(120000);
+
+    const roundId = await nextRoundId();
+    const vrf = { vrf: { oracle: oracle.programId } };
+    await expectCode(
+      () => createRound(roundId, 30, { vrf: { oracle: web3.Keypair.generate().publicKey } }),
+      "InvalidOracle",
+      "unpinned oracle"
+    );
+    await expectCode(
+      () => createRound(roundId, 30, undefined, { betaStrategy: 5 }),
+      "UnknownStrategy",
+      "unknown strategy"
+    );
+    await expectCode(
+      () => createRound(roundId, 30, undefined, { foodCount: 0 }),
+      "InvalidFoodCount",
+      "food count"
+    );
+    await expectCode(
+      () =>
+        createRound(roundId, 30, undefined, {
+          foodOdds: { goldenPct: 60, poisonPct: 30, bonusPct: 20, bonusTtl: 10 },
+        }),
+      "InvalidFoodOdds",
+      "food odds"
+    );
+    await expectCode(
+      () => createRound(roundId, 30, undefined, { mapId: 200 }),
+      "UnknownMap",
+      "unknown map"
+    );
+    await expectCode(
+      () =>
+        createRound(roundId, 30, undefined, {
+          rules: { ...DEFAULT_SETUP.rules, shrinkInterval: 0 },
+        }),
+      "InvalidRules",
+      "invalid rules"
+    );
+    await expectCode(
+      () =>
+        createRound(roundId, 30, undefined, {
+          rules: { ...DEFAULT_SETUP.rules, boardSize: 33 },
+        }),
+      "InvalidRules",
+      "board too large"
+    );
+    await createRound(roundId, 30, vrf);
+
+    const opened = await fetchRound(program, roundId);
+    expect(opened.seeded).to.equal(false);
+    expect(opened.alphaStrategy).to.equal(0);
+    expect(opened.betaStrategy).to.equal(1);
+    expect(opened.alphaBoard.length).to.equal(400);
+    expect(opened.alphaBoard.every((cell: number) => cell === 0)).to.equal(true);
+
+    await closeBettingL1(roundId, true);
+    await expectCode(() => delegateRound(roundId), "RoundNotSeeded", "delegate before seed");
+
+    await fulfillSeedL1(roundId);
+    const seeded = await fetchRound(program, roundId);
+    expect(seeded.seeded).to.equal(true);
+    expect(seeded.alphaBoard.some((cell: number) => cell !== 0)).to.equal(true);
+    // Unpacked cells line up with the header: heads and food where it says.
+    expect(seeded.alphaBoard[seeded.alphaHead]).to.equal(8);
+    expect(seeded.betaBoard[seeded.betaHead]).to.equal(8);
+    expect(seeded.alphaBoard[seeded.alphaFood[0]]).to.be.oneOf([2, 10, 11, 12]);
+
+    await expectCode(
+      () =>
+        program.methods
+          .fulfillRoundSeed(Array.from(new Uint8Array(32)))
+          .accountsPartial({
+            vrfIdentity: adminWallet.publicKey,
+            round: roundPda(roundId),
+          })
+          .rpc(),
+      "InvalidOracle",
+      "spoofed oracle callback"
+    );
+  });
+
+  it("seeds a commit-reveal round only from the committed secret", async function () {
+    this.timeout(120000);
+
+    const secret = randomBytes(32);
+    const commitment = Array.from(createHash("sha256").update(secret).digest());
+
+    const roundId = await nextRoundId();
+    await createRound(roundId, 30, { commitReveal: { commitment } });
+    await closeBettingL1(roundId);
+
+    await expectCode(
+      () => revealSeedL1(roundId, randomBytes(32)),
+      "InvalidSeedReveal",
+      "wrong preimage"
+    );
+    await expectCode(
+      () =>
+        program.methods
+          .voidRound(roundId)
+          .accountsPartial({
+            signer: adminWallet.publicKey,
+            round: roundPda(roundId),
+          })
+          .rpc(),
+      "SeedDeadlineNotReached",
+      "void before deadline"
+    );
+
+    await waitForSeedSlot(roundId);
+    await expectCode(
+      () => seedFromEntropyL1(roundId),
+      "InvalidSeedSource",
+      "entropy seeding a commit-reveal round"
+    );
+    await revealSeedL1(roundId, secret);
+    const seeded = await fetchRound(program, roundId);
+    expect(seeded.seeded).to.equal(true);
+    expect(seeded.alphaBoard.some((cell: number) => cell !== 0)).to.equal(true);
+  });
+
+  it("folds bettor entropy into the round and seeds from it at close", async function () {
+    this.timeout(120000);
+
+    const roundId = await nextRoundId();
+    // Largest board, so the biggest round account is exercised too.
+    await createRound(roundId, 30, { bettorEntropy: {} }, {
+      rules: { ...DEFAULT_SETUP.rules, boardSize: 32 },
+    });
+    await placeBetL1(
+      roundId,
+      adminWallet.publicKey,
+      MIN_BET,
+      { alpha: {} },
+      undefined,
+      Array.from(randomBytes(32))
+    );
+
+    const open = await fetchRound(program, roundId);
+    expect(open.entropyAccumulator.some((byte: number) => byte !== 0)).to.equal(true);
+
+    await closeBettingL1(roundId);
+    const closed = await fetchRound(program, roundId);
+    expect(closed.seeded).to.equal(false);
+
+    await waitForSeedSlot(roundId);
+    await seedFromEntropyL1(roundId);
+    const seeded = await fetchRound(program, roundId);
+    expect(seeded.seeded).to.equal(true);
+    expect(seeded.alphaBoard.length).to.equal(32 * 32);
+    expect(seeded.alphaBoard.some((cell: number) => cell !== 0)).to.equal(true);
+  });
+
   erOnly("enforces bet limits, supports top-up, and locks bet choice", async function () {
     this.timeout(240000);
 
@@ -512,13 +1014,13 @@ describe("magic_bet full integration", () => {
 
     await expectCode(
       () => placeBetL1(roundId, adminWallet.publicKey, new BN(1), { alpha: {} }),
-      6008,
+      "BetAmountTooLow",
       "min bet"
     );
 
     await expectCode(
       () => placeBetL1(roundId, adminWallet.publicKey, new BN(1_500_000_000), { alpha: {} }),
-      6009,
+      "BetAmountTooHigh",
       "max bet"
     );
 
@@ -530,16 +1032,17 @@ describe("magic_bet full integration", () => {
 
     await expectCode(
       () => placeBetL1(roundId, adminWallet.publicKey, MIN_BET, { beta: {} }),
-      6010,
+      "BetChoiceImmutable",
       "choice immutable"
     );
 
     await closeBettingL1(roundId);
+    await seedRoundL1(roundId);
     await delegateRound(roundId);
     const settleSig = await settleEr(roundId);
     const settledRound = await waitForSettledL1(roundId, settleSig);
 
-    if (enumKey(settledRound.winner) === "alpha") {
+    if (settledRound.winner === "alpha") {
       await claimAs(roundId, adminWallet.publicKey);
     }
 
@@ -570,17 +1073,26 @@ describe("magic_bet full integration", () => {
       );
 
       await closeBettingL1(roundId);
+      await seedRoundL1(roundId);
       await delegateRound(roundId);
       await executeMovesEr(roundId, 120);
 
       const settleSig = await settleEr(roundId);
       const settledRound = await waitForSettledL1(roundId, settleSig);
-      const winner = enumKey(settledRound.winner);
+      const winner = settledRound.winner;
+
+      // The move log is committed with the round: genesis plus one entry per tick.
+      const moveLog = await fetchMoveLog(program, roundId);
+      expect(moveLog.full).to.equal(false);
+      expect(moveLog.moveCount).to.equal(settledRound.moveCount);
+      expect(moveLog.entries.length).to.equal(settledRound.moveCount + 1);
+      expect(moveLog.entries[0].food).to.have.length(2 * settledRound.foodCount);
+      expect(moveLog.entries[1].alpha.outcome).to.equal("moved");
 
       if (winner === "draw") {
         await expectCode(
           () => claimAs(roundId, adminWallet.publicKey),
-          6014,
+          "DrawNoPayout",
           "draw claim blocked"
         );
 
@@ -604,13 +1116,13 @@ describe("magic_bet full integration", () => {
 
       await expectCode(
         () => claimAs(roundId, loserPubkey, loserSigner),
-        6013,
+        "DidNotWin",
         "loser claim blocked"
       );
 
       await expectCode(
         () => claimAs(roundId, winnerPubkey, winnerSigner),
-        6012,
+        "AlreadyClaimed",
         "double claim blocked"
       );
 
@@ -621,4 +1133,103 @@ describe("magic_bet full integration", () => {
 
     expect(nonDrawWinner, "No non-draw round found across attempts").to.not.equal(null);
   });
+
+  // The costliest ticks: a 32x32 board, path-searching AIs, every food slot
+  // holding bonus food that expires each move and is mirrored onto both
+  // boards, and a wall closing in from move 20.
+  erOnly("keeps worst-case 32x32 ticks under the tick compute reserve", async function () {
+    this.timeout(300000);
+
+    const roundId = await nextRoundId();
+    await createRound(roundId, 30, undefined, {
+      alphaStrategy: 2,
+      betaStrategy: 3,
+      foodCount: 4,
+      foodOdds: { goldenPct: 0, poisonPct: 0, bonusPct: 100, bonusTtl: 1 },
+      rules: {
+        ...DEFAULT_SETUP.rules,
+        boardSize: 32,
+        foodMirrorUntil: 300,
+        shrinkStart: 20,
+        shrinkInterval: 5,
+      },
+    });
+    await placeBetL1(roundId, adminWallet.publicKey, MIN_BET, { alpha: {} });
+    await closeBettingL1(roundId);
+    await seedRoundL1(roundId);
+    await delegateRound(roundId);
+    await executeMovesEr(roundId, 300, TICK_COMPUTE_RESERVE);
+
+    const settleSig = await settleEr(roundId);
+    const settledRound = await waitForSettledL1(roundId, settleSig);
+    if (settledRound.winner === "alpha") {
+      await claimAs(roundId, adminWallet.publicKey);
+    }
+    await closeBetByAgent(roundId, adminWallet.publicKey);
+    await sweepVaultByAgent(roundId);
+  });
+
+  erOnly("fast-forwards a round with batched execute_moves", async function () {
+    this.timeout(240000);
+
+    const roundId = await nextRoundId();
+    await createRound(roundId, 45);
+    await placeBetL1(roundId, adminWallet.publicKey, MIN_BET, { alpha: {} });
+    await closeBettingL1(roundId);
+    await seedRoundL1(roundId);
+    await delegateRound(roundId);
+
+    // Like the crank, batches ask for the whole transaction budget; the
+    // program runs as many ticks as fit above its per-tick reserve.
+    const executeMoves = (count: number) =>
+      erProgram.methods
+        .executeMoves(roundId, count)
+        .accountsPartial({
+          signer: adminWallet.publicKey,
+          config: configPda,
+          round: roundPda(roundId),
+          moveLog: moveLogPda(roundId),
+        })
+        .preInstructions([
+          web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+        ])
+        .rpc();
+
+    await expectCode(() => executeMoves(0), "InvalidMoveCount", "empty batch");
+    await expectCode(() => executeMoves(17), "InvalidMoveCount", "batch over the cap");
+
+    // The state hash chains from the previous one over the frame clients see.
+    const before = await fetchRound(erProgram, roundId);
+    await executeMoves(1);
+    let round = await fetchRound(erProgram, roundId);
+    expect(round.moveCount).to.equal(before.moveCount + 1);
+    expect(
+      frameHash(Uint8Array.from(before.stateHash), round).equals(Buffer.from(round.stateHash))
+    ).to.equal(true);
+    while (!round.winner) {
+      const signature = await executeMoves(16);
+      const tx = await erConnection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      // Return data is the u8 tick count, base64-encoded.
+      const [encoded] = tx!.meta!.returnData!.data;
+      const ticks = Buffer.from(encoded, "base64")[0];
+
+      const next = await fetchRound(erProgram, roundId);
+      expect(next.moveCount - round.moveCount).to.equal(ticks);
+      expect(ticks).to.be.within(1, 16);
+      round = next;
+    }
+
+    await expectCode(() => executeMoves(1), "RoundAlreadyResolved", "batch after resolution");
+
+    const settleSig = await settleEr(roundId);
+    const settledRound = await waitForSettledL1(roundId, settleSig);
+    if (settledRound.winner === "alpha") {
+      await claimAs(roundId, adminWallet.publicKey);
+    }
+    await closeBetByAgent(roundId, adminWallet.publicKey);
+    await sweepVaultByAgent(roundId);
+  });
 });